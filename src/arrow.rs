@@ -1,11 +1,25 @@
 use macroquad::prelude::*;
 
-use crate::grid::*;
+use arrow_puzzle::grid::*;
+
+pub fn neon_color(color: NeonColor) -> Color {
+    match color {
+        NeonColor::Green => Color::new(0.0, 1.0, 0.0, 1.0),
+        NeonColor::Magenta => Color::new(1.0, 0.0, 1.0, 1.0),
+        NeonColor::Cyan => Color::new(0.0, 1.0, 1.0, 1.0),
+        NeonColor::Yellow => Color::new(1.0, 1.0, 0.0, 1.0),
+        NeonColor::Orange => Color::new(1.0, 0.5, 0.0, 1.0),
+        NeonColor::Purple => Color::new(0.5, 0.0, 1.0, 1.0),
+        NeonColor::Pink => Color::new(1.0, 0.0, 0.5, 1.0),
+        NeonColor::Blue => Color::new(0.0, 0.5, 1.0, 1.0)
+    }
+}
 
 pub fn draw_arrow(x: f32, y: f32, arrow: &Arrow, cell_size: f32) {
+    let color = neon_color(arrow.color);
     let center = vec2(x + cell_size / 2.0, y + cell_size / 2.0);
 
-    let size = 20.0;
+    let size = cell_size * 0.25;
 
     match arrow.dir {
         Direction::Right => {
@@ -13,7 +27,7 @@ pub fn draw_arrow(x: f32, y: f32, arrow: &Arrow, cell_size: f32) {
                 center + vec2(-size, -size),
                 center + vec2(-size, size),
                 center + vec2(size, 0.0),
-                arrow.color
+                color
             );
         }
 
@@ -22,7 +36,7 @@ pub fn draw_arrow(x: f32, y: f32, arrow: &Arrow, cell_size: f32) {
                 center + vec2(size, -size),
                 center + vec2(size, size),
                 center + vec2(-size, 0.0),
-                arrow.color
+                color
             );
         }
 
@@ -31,7 +45,7 @@ pub fn draw_arrow(x: f32, y: f32, arrow: &Arrow, cell_size: f32) {
                 center + vec2(-size, size),
                 center + vec2(size, size),
                 center + vec2(0.0, -size),
-                arrow.color
+                color
             );
         }
 
@@ -40,23 +54,28 @@ pub fn draw_arrow(x: f32, y: f32, arrow: &Arrow, cell_size: f32) {
                 center + vec2(-size, -size),
                 center + vec2(size, -size),
                 center + vec2(0.0, size),
-                arrow.color
+                color
             );
         }
     }
 }
 
-pub fn draw_arrow_grid(grid: &Grid, grid_size: usize, cell_size: f32, offset: Vec2) {
-    for y in 0..grid_size {
-        for x in 0..grid_size {
-            let px = offset.x + x as f32 * cell_size;
-            let py = offset.y + y as f32 * cell_size;
+// Hinted arrow gets a pulsing outline in its own color
+pub fn draw_arrow_grid(grid: &Grid, cell_size: f32, offset: Vec2, hint: Option<(usize, usize)>) {
+    for (x, y, arrow) in grid.occupied() {
+        let px = offset.x + x as f32 * cell_size;
+        let py = offset.y + y as f32 * cell_size;
 
-            // draw_rectangle_lines(px, py, cell_size, cell_size, 2.0, BLACK);
+        // draw_rectangle_lines(px, py, cell_size, cell_size, 2.0, BLACK);
 
-            if let Some(arrow) = &grid[y][x] {
-                draw_arrow(px, py, arrow, cell_size);
-            }
+        if hint == Some((x, y)) {
+            let pulse = ((get_time() * 6.0).sin() as f32 + 1.0) / 2.0;
+            let inset = cell_size * 0.1;
+            let color = Color { a: 0.3 + pulse * 0.7, ..neon_color(arrow.color) };
+
+            draw_rectangle_lines(px + inset, py + inset, cell_size - inset * 2.0, cell_size - inset * 2.0, 3.0, color);
         }
+
+        draw_arrow(px, py, arrow, cell_size);
     }
 }
@@ -0,0 +1,46 @@
+use std::borrow::Cow;
+use std::fs;
+
+use arrow_puzzle::paths::Paths;
+
+// Asset bytes from the assets folder when the file is there, so assets can be swapped without a rebuild, otherwise the copy built into the binary
+pub fn load_asset(paths: &Paths, relative: &str) -> Option<Cow<'static, [u8]>> {
+    match fs::read(paths.asset(relative)) {
+        Ok(bytes) => Some(Cow::Owned(bytes)),
+        Err(_) => embedded(relative).map(Cow::Borrowed)
+    }
+}
+
+#[cfg(feature = "embedded-assets")]
+macro_rules! embed_assets {
+    ($($relative:literal),* $(,)?) => {
+        fn embedded(relative: &str) -> Option<&'static [u8]> {
+            match relative {
+                $($relative => Some(include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/", $relative))),)*
+                _ => None
+            }
+        }
+    };
+}
+
+// The music is not part of the source tree, it can only ever come from the assets folder
+#[cfg(feature = "embedded-assets")]
+embed_assets!(
+    "sound_fx/beep-313342.mp3",
+    "sound_fx/error-mistake-sound-effect-incorrect-answer-437420.mp3",
+    "sound_fx/tada-234709.mp3",
+    "ui/window_background.png",
+    "ui/button_background.png",
+    "ui/button_hovered_background.png",
+    "ui/button_clicked_background.png",
+    "ui/checkbox_background.png",
+    "ui/checkbox_hovered_background.png",
+    "ui/checkbox_clicked_background.png",
+    "ui/editbox_background.png",
+    "ui/combobox_background.png"
+);
+
+#[cfg(not(feature = "embedded-assets"))]
+fn embedded(_relative: &str) -> Option<&'static [u8]> {
+    None
+}
@@ -1,76 +1,251 @@
-use kira::{AudioManager, AudioManagerSettings};
-use kira::sound::static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings};
-use std::error::Error;
+use std::env;
 
+use crate::game::GameEvent;
+
+const AUDIO_FLAG: &str = "--audio";
+const AUDIO_VAR: &str = "ARROW_PUZZLE_AUDIO";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sound {
+    Button,
+    Mistake,
+    Success,
+    Record,
+    Tick,
+    Go
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Music {
+    Menu,
+    Gameplay
+}
+
+// Something that can make the game's sounds, volumes go from 0.0 (muted) to 1.0 (full volume)
+pub trait AudioBackend {
+    fn set_volumes(&mut self, master_volume: f32, music_volume: f32, sound_fx_volume: f32);
+    fn play(&mut self, sound: Sound);
+    fn start_music(&mut self, music: Music, fade: f32);
+    fn stop_music(&mut self, fade: f32);
+
+    // Sounds played so far, only the recording backend keeps them
+    fn played(&self) -> &[Sound] {
+        &[]
+    }
+}
+
+// Volumes and the backend that plays the sounds, muted sounds never reach the backend
 pub struct Audio {
-    manager: AudioManager,
-    pub sound_fx: bool,
-    pub music: bool,
-    button_sound_fx: StaticSoundData,
-    mistake_sound_fx: StaticSoundData,
-    success_sound_fx: StaticSoundData,
-    background_menu_music: StaticSoundData,
-    background_gameplay_music: StaticSoundData,
-    music_handle: Option<StaticSoundHandle>
+    backend: Box<dyn AudioBackend>,
+    master_volume: f32,
+    music_volume: f32,
+    sound_fx_volume: f32
 }
 
 impl Audio {
-    pub fn new(sound_fx: bool, music: bool) -> Result<Self, Box<dyn Error>> {
-        let manager = AudioManager::new(AudioManagerSettings::default())?;
+    // Picks the backend from `--audio <kira|null|recording>` or ARROW_PUZZLE_AUDIO, kira is whatever backend the frontend plays through.
+    // An unknown backend is added to the warnings for the player to see, as is anything kira reports
+    pub fn new(kira: impl FnOnce(&mut Vec<String>) -> Box<dyn AudioBackend>, master_volume: f32, music_volume: f32, sound_fx_volume: f32, warnings: &mut Vec<String>) -> Self {
+        let backend: Box<dyn AudioBackend> = match requested_backend().as_deref() {
+            Some("null") => Box::new(NullBackend),
+            Some("recording") => Box::new(RecordingBackend::new()),
+            Some(other) if other != "kira" => {
+                warnings.push(format!("Unknown audio backend {}, using kira", other));
+                kira(warnings)
+            }
+            _ => kira(warnings)
+        };
+
+        Audio::with_backend(backend, master_volume, music_volume, sound_fx_volume)
+    }
+
+    pub fn with_backend(backend: Box<dyn AudioBackend>, master_volume: f32, music_volume: f32, sound_fx_volume: f32) -> Self {
+        let mut audio = Self { backend, master_volume, music_volume, sound_fx_volume };
+        audio.set_volumes(master_volume, music_volume, sound_fx_volume);
+
+        audio
+    }
+
+    pub fn master_volume(&self) -> f32 {
+        self.master_volume
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        self.music_volume
+    }
 
-        let button_sound_fx = StaticSoundData::from_file("assets/sound_fx/beep-313342.mp3")?;
-        let mistake_sound_fx = StaticSoundData::from_file("assets/sound_fx/error-mistake-sound-effect-incorrect-answer-437420.mp3")?;
-        let success_sound_fx = StaticSoundData::from_file("assets/sound_fx/tada-234709.mp3")?;
+    pub fn sound_fx_volume(&self) -> f32 {
+        self.sound_fx_volume
+    }
+
+    pub fn played(&self) -> &[Sound] {
+        self.backend.played()
+    }
 
-        let background_menu_music = StaticSoundData::from_file("assets/music/phatphrogstudio-cyber-attack-datastorm-rebellion-477469.mp3")?
-            .with_settings(StaticSoundSettings::default().loop_region(..));
-        let background_gameplay_music = StaticSoundData::from_file("assets/music/phatphrogstudio-internal-fury-furyx27s-dance-477470.mp3")?
-            .with_settings(StaticSoundSettings::default().loop_region(..));
+    pub fn set_volumes(&mut self, master_volume: f32, music_volume: f32, sound_fx_volume: f32) {
+        self.master_volume = master_volume.clamp(0.0, 1.0);
+        self.music_volume = music_volume.clamp(0.0, 1.0);
+        self.sound_fx_volume = sound_fx_volume.clamp(0.0, 1.0);
 
-        Ok(Self {manager, sound_fx, music, button_sound_fx, mistake_sound_fx, success_sound_fx, background_menu_music, background_gameplay_music, music_handle: None})
+        self.backend.set_volumes(self.master_volume, self.music_volume, self.sound_fx_volume);
     }
 
     pub fn play_button(&mut self) {
-        if self.sound_fx {
-            let _ = self.manager.play(self.button_sound_fx.clone());
-        }
+        self.play(Sound::Button);
     }
 
     pub fn play_mistake(&mut self) {
-        if self.sound_fx {
-            let _ = self.manager.play(self.mistake_sound_fx.clone());
-        }
+        self.play(Sound::Mistake);
     }
 
     pub fn play_success(&mut self) {
-        if self.sound_fx {
-            let _ = self.manager.play(self.success_sound_fx.clone());
+        self.play(Sound::Success);
+    }
+
+    pub fn play_record(&mut self) {
+        self.play(Sound::Record);
+    }
+
+    pub fn play_tick(&mut self) {
+        self.play(Sound::Tick);
+    }
+
+    pub fn play_go(&mut self) {
+        self.play(Sound::Go);
+    }
+
+    // Sounds that follow straight from a game event, the end of a run picks its sound once it is compared to the saved runs
+    pub fn play_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::CountdownTick => self.play_tick(),
+            GameEvent::CountdownGo => self.play_go(),
+            GameEvent::Mistake => self.play_mistake(),
+            _ => {}
         }
     }
 
-    pub fn start_background_menu_music(&mut self) {
-        if self.music {
-            if self.music_handle.is_none() {
-                if let Ok(handle) = self.manager.play(self.background_menu_music.clone()) {
-                    self.music_handle = Some(handle);
-                }
-            }
+    pub fn start_background_menu_music(&mut self, fade: f32) {
+        self.start_music(Music::Menu, fade);
+    }
+
+    pub fn start_background_gameplay_music(&mut self, fade: f32) {
+        self.start_music(Music::Gameplay, fade);
+    }
+
+    // The stopped track keeps fading out while the next one fades in
+    pub fn stop_music(&mut self, fade: f32) {
+        self.backend.stop_music(fade);
+    }
+
+    fn play(&mut self, sound: Sound) {
+        if self.sound_fx_volume > 0.0 {
+            self.backend.play(sound);
         }
     }
 
-    pub fn start_background_gameplay_music(&mut self) {
-        if self.music {
-            if self.music_handle.is_none() {
-                if let Ok(handle) = self.manager.play(self.background_gameplay_music.clone()) {
-                    self.music_handle = Some(handle);
-                }
-            }
+    fn start_music(&mut self, music: Music, fade: f32) {
+        if self.music_volume > 0.0 {
+            self.backend.start_music(music, fade);
         }
     }
+}
+
+fn requested_backend() -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+
+    args.iter().position(|arg| arg == AUDIO_FLAG).and_then(|i| args.get(i + 1)).cloned()
+        .or_else(|| env::var(AUDIO_VAR).ok())
+}
+
+// Plays nothing, for machines without a sound device
+pub struct NullBackend;
+
+impl AudioBackend for NullBackend {
+    fn set_volumes(&mut self, _master_volume: f32, _music_volume: f32, _sound_fx_volume: f32) {}
+    fn play(&mut self, _sound: Sound) {}
+    fn start_music(&mut self, _music: Music, _fade: f32) {}
+    fn stop_music(&mut self, _fade: f32) {}
+}
+
+// Plays nothing but remembers every sound, so a run can be checked for the sounds it triggered
+pub struct RecordingBackend {
+    played: Vec<Sound>,
+    music: Option<Music>
+}
+
+impl RecordingBackend {
+    pub fn new() -> RecordingBackend {
+        RecordingBackend { played: Vec::new(), music: None }
+    }
+}
+
+impl Default for RecordingBackend {
+    fn default() -> RecordingBackend {
+        RecordingBackend::new()
+    }
+}
+
+impl AudioBackend for RecordingBackend {
+    fn set_volumes(&mut self, _master_volume: f32, _music_volume: f32, _sound_fx_volume: f32) {}
 
-    pub fn stop_music(&mut self) {
-        if let Some(mut handle) = self.music_handle.take() {
-            let _ = handle.stop(Default::default());
+    fn play(&mut self, sound: Sound) {
+        self.played.push(sound);
+    }
+
+    fn start_music(&mut self, music: Music, _fade: f32) {
+        if self.music.is_none() {
+            self.music = Some(music);
         }
     }
+
+    fn stop_music(&mut self, _fade: f32) {
+        self.music = None;
+    }
+
+    fn played(&self) -> &[Sound] {
+        &self.played
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::Difficulty;
+    use crate::game::Game;
+    use crate::grid::*;
+    use crate::mode::Mode;
+
+    fn recording(sound_fx_volume: f32) -> Audio {
+        Audio::with_backend(Box::new(RecordingBackend::new()), 1.0, 1.0, sound_fx_volume)
+    }
+
+    // Ticks through the countdown and clicks an arrow that is blocked, passing every event on to the audio
+    fn play(audio: &mut Audio) {
+        let mut game = Game::with_seed(Mode::Survival, 7, Difficulty::Normal, 4, 4, 30.0);
+
+        game.play_countdown().iter().for_each(|event| audio.play_event(event));
+
+        let (x, y) = game.grid.occupied().map(|(x, y, _)| (x, y)).find(|&(x, y)| !can_remove(&game.grid, x, y)).unwrap();
+
+        game.click(x, y).iter().for_each(|event| audio.play_event(event));
+    }
+
+    #[test]
+    fn game_events_play_their_sounds() {
+        let mut audio = recording(1.0);
+
+        play(&mut audio);
+
+        assert_eq!(audio.played(), [Sound::Tick, Sound::Tick, Sound::Tick, Sound::Go, Sound::Mistake]);
+    }
+
+    #[test]
+    fn muted_sounds_are_not_played() {
+        let mut audio = recording(0.0);
+
+        play(&mut audio);
+        audio.play_button();
+
+        assert!(audio.played().is_empty());
+    }
 }
@@ -0,0 +1,70 @@
+const COUNTDOWN_SECONDS: f32 = 3.0;
+const GO_SECONDS: f32 = 0.6; // How long "GO" stays on screen after the run has started
+
+pub enum CountdownCue {
+    Tick,
+    Go
+}
+
+#[derive(Clone)]
+pub struct Countdown {
+    remaining: f32,
+    shown: Option<u32>
+}
+
+impl Countdown {
+    pub fn new() -> Countdown {
+        Countdown { remaining: COUNTDOWN_SECONDS + GO_SECONDS, shown: None }
+    }
+
+    // A countdown that has already finished, used before the first run starts
+    pub fn finished() -> Countdown {
+        Countdown { remaining: 0.0, shown: Some(0) }
+    }
+
+    // Input and the timer stay frozen while this is true
+    pub fn is_running(&self) -> bool {
+        self.remaining > GO_SECONDS
+    }
+
+    pub fn update(&mut self, dt: f32) -> Option<CountdownCue> {
+        if self.remaining <= 0.0 {
+            return None;
+        }
+
+        self.remaining = (self.remaining - dt).max(0.0);
+
+        // Whole seconds left, 0 once "GO" is showing
+        let second = (self.remaining - GO_SECONDS).max(0.0).ceil() as u32;
+
+        if self.shown == Some(second) {
+            return None;
+        }
+
+        self.shown = Some(second);
+
+        if second == 0 {
+            Some(CountdownCue::Go)
+        } else {
+            Some(CountdownCue::Tick)
+        }
+    }
+
+    pub fn label(&self) -> Option<String> {
+        if self.remaining <= 0.0 {
+            return None;
+        }
+
+        match self.shown {
+            Some(0) => Some("GO".to_string()),
+            Some(second) => Some(second.to_string()),
+            None => None
+        }
+    }
+}
+
+impl Default for Countdown {
+    fn default() -> Countdown {
+        Countdown::new()
+    }
+}
@@ -0,0 +1,211 @@
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::grid::*;
+use crate::solver::removable_arrows;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard
+}
+
+impl Difficulty {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard"
+        }
+    }
+
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy
+        }
+    }
+
+    // Range of ratings a grid of this difficulty falls into
+    fn band(&self) -> (f32, f32) {
+        match self {
+            Difficulty::Easy => (0.0, EASY_MAX),
+            Difficulty::Normal => (EASY_MAX, NORMAL_MAX),
+            Difficulty::Hard => (NORMAL_MAX, 1.0)
+        }
+    }
+
+    pub fn contains(&self, rating: f32) -> bool {
+        let (min, max) = self.band();
+
+        rating >= min && rating <= max
+    }
+
+    pub fn distance(&self, rating: f32) -> f32 {
+        let (min, max) = self.band();
+
+        (min - rating).max(rating - max).max(0.0)
+    }
+}
+
+// Ratings are relative to random grids of the same size, so each band holds a third of them on every size
+const EASY_MAX: f32 = 1.0 / 3.0;
+const NORMAL_MAX: f32 = 2.0 / 3.0;
+const CALIBRATION_GRIDS: u32 = 300;
+const CALIBRATION_SEED: u64 = 0xCA11_B8A7_E000_0000;
+
+// Sorted raw scores of the calibration grids of one grid size
+type Calibration = ((usize, usize), Vec<f32>);
+
+// Generated the first time a grid size is rated
+static CALIBRATION: Mutex<Vec<Calibration>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone)]
+pub struct DifficultyReport {
+    pub arrows: usize,
+    pub free_arrows: usize,
+    pub chain_depth: usize,
+    pub branching: f32,
+    pub grid_size: f32
+}
+
+impl DifficultyReport {
+    // Share of arrows blocked at the start, how many rounds it takes to unblock them and how few arrows are free on average, all 0.0 - 1.0
+    // Only comparable between grids of the same size, see rate_grid
+    pub fn score(&self) -> f32 {
+        let blocked = if self.arrows > 0 { 1.0 - self.free_arrows as f32 / self.arrows as f32 } else { 0.0 };
+        let depth = (self.chain_depth.saturating_sub(1) as f32 / self.grid_size).min(1.0);
+
+        0.4 * blocked + 0.3 * depth + 0.3 * (1.0 - self.branching)
+    }
+}
+
+// Share of random grids of the same size that are easier, ties count half
+pub fn rate_grid(grid: &Grid) -> f32 {
+    let score = analyse_grid(grid).score();
+    let samples = calibration(grid.width(), grid.height());
+    let easier = samples.iter().filter(|&&sample| sample < score).count() as f32;
+    let equal = samples.iter().filter(|&&sample| sample == score).count() as f32;
+
+    (easier + equal / 2.0) / samples.len() as f32
+}
+
+fn calibration(width: usize, height: usize) -> Vec<f32> {
+    let mut cache = CALIBRATION.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    if let Some((_, samples)) = cache.iter().find(|(size, _)| *size == (width, height)) {
+        return samples.clone();
+    }
+
+    let mut samples: Vec<f32> = (0..CALIBRATION_GRIDS)
+        .map(|i| analyse_grid(&generate_grid_seeded(width, height, board_seed(CALIBRATION_SEED, i), None)).score())
+        .collect();
+
+    samples.sort_by(f32::total_cmp);
+    cache.push(((width, height), samples.clone()));
+
+    samples
+}
+
+// Removes every free arrow at once, layer by layer, the same way the solver does
+pub fn analyse_grid(grid: &Grid) -> DifficultyReport {
+    let branching = branching_factor(grid);
+    let mut grid = grid.clone();
+    let mut layers: Vec<usize> = Vec::new();
+
+    loop {
+        let free = removable_arrows(&grid);
+
+        if free.is_empty() {
+            break;
+        }
+
+        layers.push(free.len());
+
+        for (x, y) in free {
+            grid.remove(x, y);
+        }
+    }
+
+    let arrows: usize = layers.iter().sum();
+    let free_arrows = layers.first().copied().unwrap_or(0);
+    let chain_depth = layers.len();
+
+    // Chains on a rectangular grid are bounded by both sides, so rate against their average
+    let grid_size = (grid.width() + grid.height()) as f32 / 2.0;
+
+    DifficultyReport { arrows, free_arrows, chain_depth, branching, grid_size }
+}
+
+// Average share of the remaining arrows that can be removed, over every move of a one arrow at a time clear
+fn branching_factor(grid: &Grid) -> f32 {
+    let mut grid = grid.clone();
+    let mut total = 0.0;
+    let mut moves = 0;
+
+    while let Some((x, y)) = find_hint(&grid) {
+        let left = grid.occupied().count();
+
+        total += removable_arrows(&grid).len() as f32 / left as f32;
+        moves += 1;
+        grid.remove(x, y);
+    }
+
+    if moves > 0 { total / moves as f32 } else { 1.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZES: [(usize, usize); 12] = [(4, 4), (5, 5), (6, 6), (7, 7), (8, 8), (9, 9), (10, 10), (11, 11), (12, 12), (4, 12), (12, 4), (8, 5)];
+
+    // Fresh grids, not the calibration ones, should split into roughly equal bands on every size
+    #[test]
+    fn bands_split_random_grids_into_thirds() {
+        for (width, height) in SIZES {
+            let mut counts = [0; 3];
+
+            for i in 0..150 {
+                let rating = rate_grid(&generate_grid_seeded(width, height, board_seed(1, i), None));
+                let band = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard].iter().position(|difficulty| difficulty.contains(rating)).unwrap();
+
+                counts[band] += 1;
+            }
+
+            for count in counts {
+                assert!((30..=70).contains(&count), "{}x{} bands {:?}", width, height, counts);
+            }
+        }
+    }
+
+    #[test]
+    fn generated_grids_land_in_their_band() {
+        for (width, height) in SIZES {
+            for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+                for seed in 0..5 {
+                    let grid = generate_grid_seeded(width, height, seed, Some(difficulty));
+
+                    assert!(difficulty.contains(rate_grid(&grid)), "{}x{} {:?} seed {}", width, height, difficulty, seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn branching_is_lower_when_fewer_arrows_are_free() {
+        let mut open = Grid::new(3, 1);
+        let mut blocked = Grid::new(3, 1);
+
+        for x in 0..3 {
+            open.set(x, 0, Some(Arrow { dir: Direction::Up, color: NeonColor::Green }));
+            blocked.set(x, 0, Some(Arrow { dir: Direction::Right, color: NeonColor::Green }));
+        }
+
+        assert_eq!(analyse_grid(&open).branching, 1.0);
+        assert!(analyse_grid(&blocked).branching < 1.0);
+    }
+}
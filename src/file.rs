@@ -1,39 +1,243 @@
 use serde::{Deserialize, Serialize};
-use std::fs::File;
-use std::io::{BufReader, Write, Result};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use chrono::Local;
+use std::fs::{self, File};
+use std::io::{BufReader, ErrorKind, Write, Result};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 
-#[derive(Serialize, Deserialize, Debug)]
+use crate::difficulty::Difficulty;
+use crate::grid::{DEFAULT_GRID_SIZE, MIN_GRID_SIZE, MAX_GRID_SIZE};
+use crate::mode::Mode;
+use crate::replay::Replay;
+use crate::leaderboard::timer_duration;
+
+// Each migration upgrades a file by one version, MIGRATIONS[n] turns version n into n + 1
+type Migration = fn(&mut Value);
+
+const PROFILES_MIGRATIONS: [Migration; 2] = [migrate_profiles_v1, migrate_profiles_v2];
+const SCOREBOARD_MIGRATIONS: [Migration; 1] = [migrate_scoreboard_v1];
+const PROFILES_VERSION: u32 = PROFILES_MIGRATIONS.len() as u32;
+const SCOREBOARD_VERSION: u32 = SCOREBOARD_MIGRATIONS.len() as u32;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SettingsFile {
+    #[serde(default)]
     pub player_name: String,
+    #[serde(default = "default_timer_mode_duration")]
     pub timer_mode_duration: f32,
-    pub sound_fx: bool,
-    pub music: bool
+    #[serde(default = "default_volume")]
+    pub master_volume: f32,
+    #[serde(default = "default_volume")]
+    pub music_volume: f32,
+    #[serde(default = "default_volume")]
+    pub sound_fx_volume: f32,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default = "default_grid_size")]
+    pub survival_grid_width: usize,
+    #[serde(default = "default_grid_size")]
+    pub survival_grid_height: usize,
+    #[serde(default = "default_grid_size")]
+    pub timer_grid_width: usize,
+    #[serde(default = "default_grid_size")]
+    pub timer_grid_height: usize,
+    #[serde(default = "default_grid_size")]
+    pub math_grid_width: usize,
+    #[serde(default = "default_grid_size")]
+    pub math_grid_height: usize,
+    #[serde(default = "default_grid_size")]
+    pub zen_grid_width: usize,
+    #[serde(default = "default_grid_size")]
+    pub zen_grid_height: usize,
+    #[serde(default = "default_transition_duration")]
+    pub transition_duration: f32
+}
+
+impl Default for SettingsFile {
+    fn default() -> SettingsFile {
+        SettingsFile {
+            player_name: "".to_string(),
+            timer_mode_duration: default_timer_mode_duration(),
+            master_volume: default_volume(),
+            music_volume: default_volume(),
+            sound_fx_volume: default_volume(),
+            difficulty: Difficulty::Normal,
+            survival_grid_width: DEFAULT_GRID_SIZE,
+            survival_grid_height: DEFAULT_GRID_SIZE,
+            timer_grid_width: DEFAULT_GRID_SIZE,
+            timer_grid_height: DEFAULT_GRID_SIZE,
+            math_grid_width: DEFAULT_GRID_SIZE,
+            math_grid_height: DEFAULT_GRID_SIZE,
+            zen_grid_width: DEFAULT_GRID_SIZE,
+            zen_grid_height: DEFAULT_GRID_SIZE,
+            transition_duration: default_transition_duration()
+        }
+    }
+}
+
+impl SettingsFile {
+    // A hand edited file can hold sizes outside the allowed range
+    pub fn grid_size(&self, mode: Mode) -> (usize, usize) {
+        let (width, height) = match mode {
+            Mode::Survival => (self.survival_grid_width, self.survival_grid_height),
+            Mode::Timer => (self.timer_grid_width, self.timer_grid_height),
+            Mode::Math => (self.math_grid_width, self.math_grid_height),
+            Mode::Zen => (self.zen_grid_width, self.zen_grid_height)
+        };
+
+        (width.clamp(MIN_GRID_SIZE, MAX_GRID_SIZE), height.clamp(MIN_GRID_SIZE, MAX_GRID_SIZE))
+    }
+}
+
+// Every profile keeps its own settings, the player name of those settings is the profile name
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProfilesFile {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub active: usize,
+    #[serde(default)]
+    pub profiles: Vec<SettingsFile>
+}
+
+impl Default for ProfilesFile {
+    fn default() -> ProfilesFile {
+        ProfilesFile { version: PROFILES_VERSION, active: 0, profiles: vec![SettingsFile::default()] }
+    }
+}
+
+impl ProfilesFile {
+    pub fn active(&self) -> &SettingsFile {
+        &self.profiles[self.active]
+    }
+
+    pub fn set_active(&mut self, settings: SettingsFile) {
+        self.profiles[self.active] = settings;
+    }
+
+    pub fn name_taken(&self, name: &str) -> bool {
+        self.profiles.iter().enumerate().any(|(i, profile)| i != self.active && profile.player_name == name)
+    }
+
+    // Switches to a new profile with default settings and a free "Player n" name
+    pub fn add_profile(&mut self) {
+        let name = (1..).map(|n| format!("Player {}", n)).find(|name| !self.profiles.iter().any(|profile| profile.player_name == *name)).unwrap();
+
+        self.profiles.push(SettingsFile { player_name: name, ..SettingsFile::default() });
+        self.active = self.profiles.len() - 1;
+    }
+
+    // Steps through the profiles, wrapping around at both ends
+    pub fn step_active(&mut self, step: i32) {
+        self.active = (self.active as i32 + step).rem_euclid(self.profiles.len() as i32) as usize;
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SaveData {
+    #[serde(default)]
     pub player_name: String,
+    #[serde(default)]
     pub gamemode: String,
+    #[serde(default)]
     pub time: f32,
+    #[serde(default)]
     pub score: i32,
-    pub datetime: String
+    #[serde(default)]
+    pub datetime: String,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default = "default_grid_size")]
+    pub grid_width: usize,
+    #[serde(default = "default_grid_size")]
+    pub grid_height: usize,
+    #[serde(default)]
+    pub hints_used: u32,
+    #[serde(default)]
+    pub replay: Option<String>, // File name of the replay of this run
+    #[serde(default)]
+    pub removed: u32, // Arrows or numbers taken off the board
+    #[serde(default)]
+    pub clicks: u32,
+    #[serde(default)]
+    pub mistakes: u32
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SaveFile {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
     pub games_saved: Vec<SaveData>
 }
 
-pub fn write_json<T: Serialize>(file_name: &str, data: &T) -> Result<()> {
+impl Default for SaveFile {
+    fn default() -> SaveFile {
+        SaveFile { version: SCOREBOARD_VERSION, games_saved: Vec::new() }
+    }
+}
+
+// How a finished run compares to the runs saved before it
+pub struct RunComparison {
+    pub score: i32,
+    pub previous_best: Option<i32>, // Best score of the same player on the same board
+    pub rank: usize // Position among every player's runs on the same board
+}
+
+impl RunComparison {
+    // The first run on a board has no record to break
+    pub fn is_record(&self) -> bool {
+        self.previous_best.is_some_and(|best| self.score > best)
+    }
+}
+
+fn default_grid_size() -> usize {
+    DEFAULT_GRID_SIZE
+}
+
+fn default_timer_mode_duration() -> f32 {
+    30.0
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+pub fn default_transition_duration() -> f32 {
+    0.5
+}
+
+pub fn write_json<T: Serialize>(file_name: &Path, data: &T) -> Result<()> {
     let json = serde_json::to_string_pretty(data)?;
-    let mut file = File::create(file_name)?;
 
-    file.write_all(json.as_bytes())?;
-    
+    write_atomic(file_name, json.as_bytes())
+}
+
+// Writes next to the target and renames over it once everything is on disk,
+// a crash at any point leaves either the old or the new file but never a truncated one
+fn write_atomic(file_name: &Path, bytes: &[u8]) -> Result<()> {
+    let temp_name = with_suffix(file_name, ".tmp");
+    let mut file = File::create(&temp_name)?;
+
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    fs::rename(&temp_name, file_name)?;
+
+    // The rename is only durable once the directory is synced, not every platform can open a directory so this is best effort
+    let directory = file_name.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+
+    if let Ok(directory) = File::open(directory) {
+        let _ = directory.sync_all();
+    }
+
     Ok(())
 }
 
-pub fn read_json<T: for<'de> Deserialize<'de>>(file_name: &str) -> Result<T> {
+pub fn read_json<T: for<'de> Deserialize<'de>>(file_name: &Path) -> Result<T> {
     let file = File::open(file_name)?;
     let reader = BufReader::new(file);
     let data = serde_json::from_reader(reader)?;
@@ -41,14 +245,386 @@ pub fn read_json<T: for<'de> Deserialize<'de>>(file_name: &str) -> Result<T> {
     Ok(data)
 }
 
-pub fn append_to_scoreboard(file_name: &str, save_data: SaveData) -> Result<()> {
-    let mut save_file: SaveFile = match read_json(file_name) {
-        Ok(f) => f,
-        Err(_) => SaveFile { games_saved: Vec::new() }
+// Runs the migrations a file is missing, files without a version are version 0.
+// A file that exists but can not be read is copied aside first, so saving over it later never loses data,
+// what happened to the copy is added to the warnings for the player to see
+fn read_versioned<T: DeserializeOwned>(file_name: &Path, migrations: &[Migration], warnings: &mut Vec<String>) -> Result<T> {
+    let result = read_json::<Value>(file_name).and_then(|mut value| {
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(0) as usize;
+
+        for migration in migrations.iter().skip(version) {
+            migration(&mut value);
+        }
+
+        if let Some(file) = value.as_object_mut() {
+            file.insert("version".to_string(), json!(version.max(migrations.len())));
+        }
+
+        Ok(serde_json::from_value(value)?)
+    });
+
+    if let Err(e) = &result && e.kind() != ErrorKind::NotFound {
+        // A failed copy is only reported, the caller needs to know why the file itself could not be read
+        match back_up_unreadable(file_name) {
+            Ok(Some(backup)) => warnings.push(format!("Could not read {} ({}), kept a copy as {}", file_name.display(), e, backup.display())),
+            Ok(None) => {}
+            Err(copy_error) => warnings.push(format!("Could not read {} ({}) nor keep a copy of it ({})", file_name.display(), e, copy_error))
+        }
+    }
+
+    result
+}
+
+// Copies the file to a timestamped ".bak", unless an earlier launch already kept the exact same contents
+fn back_up_unreadable(file_name: &Path) -> Result<Option<PathBuf>> {
+    let contents = fs::read(file_name)?;
+    let directory = file_name.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let prefix = format!("{}.", file_name.file_name().unwrap_or_default().to_string_lossy());
+
+    for entry in fs::read_dir(directory)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        if name.starts_with(&prefix) && name.ends_with(".bak") && fs::read(entry.path()).is_ok_and(|backup| backup == contents) {
+            return Ok(None);
+        }
+    }
+
+    let backup = with_suffix(file_name, &format!(".{}.bak", Local::now().format("%Y%m%d-%H%M%S")));
+
+    fs::write(&backup, contents)?;
+
+    Ok(Some(backup))
+}
+
+// Without a profiles file the old single settings file becomes the first profile
+pub fn read_profiles(file_name: &Path, legacy_settings_file: &Path, warnings: &mut Vec<String>) -> ProfilesFile {
+    let mut profiles: ProfilesFile = match read_versioned(file_name, &PROFILES_MIGRATIONS, warnings) {
+        Ok(profiles) => profiles,
+        Err(e) if e.kind() == ErrorKind::NotFound => read_versioned(legacy_settings_file, &PROFILES_MIGRATIONS, warnings).unwrap_or_default(),
+        Err(_) => ProfilesFile::default()
     };
-    
+
+    if profiles.profiles.is_empty() {
+        profiles.profiles.push(SettingsFile::default());
+    }
+
+    profiles.active = profiles.active.min(profiles.profiles.len() - 1);
+
+    profiles
+}
+
+// Appends to the full file name, "scoreboard.json" becomes "scoreboard.json.backup" rather than replacing the extension
+fn with_suffix(file_name: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(file_name);
+
+    name.push(suffix);
+
+    PathBuf::from(name)
+}
+
+fn scoreboard_backup(file_name: &Path) -> PathBuf {
+    with_suffix(file_name, ".backup")
+}
+
+// Falls back to the backup of the last good scoreboard when the file itself can not be read, no file yet is an empty scoreboard.
+// An error means the runs in the file would be lost if it was written over
+pub fn read_scoreboard(file_name: &Path, warnings: &mut Vec<String>) -> Result<SaveFile> {
+    match read_versioned(file_name, &SCOREBOARD_MIGRATIONS, warnings) {
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(SaveFile::default()),
+
+        Err(e) => match read_versioned(&scoreboard_backup(file_name), &SCOREBOARD_MIGRATIONS, warnings) {
+            Ok(backup) => {
+                warnings.push(format!("Recovered {} from its backup", file_name.display()));
+
+                Ok(backup)
+            }
+
+            // Starting over is only safe once a copy of the broken file is kept
+            Err(_) if back_up_unreadable(file_name).is_ok() => Ok(SaveFile::default()),
+            Err(_) => Err(e)
+        },
+
+        result => result
+    }
+}
+
+// Keeps the scoreboard that is being replaced as the backup, but only while it still reads fine
+fn write_scoreboard(file_name: &Path, save_file: &SaveFile) -> Result<()> {
+    if read_json::<SaveFile>(file_name).is_ok() {
+        write_atomic(&scoreboard_backup(file_name), &fs::read(file_name)?)?;
+    }
+
+    write_json(file_name, save_file)
+}
+
+// Version 0 was either a single settings file or the first, unversioned profiles file
+fn migrate_profiles_v1(value: &mut Value) {
+    if value.get("profiles").is_none() {
+        *value = json!({ "active": 0, "profiles": [value.take()] });
+    }
+
+    if let Some(profiles) = value.get_mut("profiles").and_then(Value::as_array_mut) {
+        profiles.iter_mut().for_each(migrate_settings);
+    }
+}
+
+// Math and Zen used to be played on the Survival size, they keep that size until the player picks their own
+fn migrate_profiles_v2(value: &mut Value) {
+    let Some(profiles) = value.get_mut("profiles").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    for settings in profiles.iter_mut().filter_map(Value::as_object_mut) {
+        for mode in ["math", "zen"] {
+            for side in ["width", "height"] {
+                if let Some(size) = settings.get(&format!("survival_grid_{}", side)).cloned() {
+                    settings.entry(format!("{}_grid_{}", mode, side)).or_insert(size);
+                }
+            }
+        }
+    }
+}
+
+// Older settings files switched sound on and off instead of storing a volume, and stored square grid sizes
+fn migrate_settings(value: &mut Value) {
+    let Some(settings) = value.as_object_mut() else {
+        return;
+    };
+
+    for (toggle, volume) in [("sound_fx", "sound_fx_volume"), ("music", "music_volume")] {
+        if let Some(enabled) = settings.remove(toggle).and_then(|v| v.as_bool()) {
+            settings.entry(volume).or_insert(json!(if enabled { 1.0 } else { 0.0 }));
+        }
+    }
+
+    split_grid_size(settings, "survival_grid_size", "survival_grid_width", "survival_grid_height");
+    split_grid_size(settings, "timer_grid_size", "timer_grid_width", "timer_grid_height");
+}
+
+// Before grids could be rectangular a single size was stored for both sides, and before any size was stored every grid was 6x6.
+// The 6 is written out so a later change of the default size does not alter old saves
+fn split_grid_size(object: &mut Map<String, Value>, size: &str, width: &str, height: &str) {
+    let size = object.remove(size).unwrap_or(json!(6));
+
+    object.entry(width).or_insert(size.clone());
+    object.entry(height).or_insert(size);
+}
+
+fn migrate_scoreboard_v1(value: &mut Value) {
+    let Some(entries) = value.get_mut("games_saved").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    for entry in entries.iter_mut().filter_map(Value::as_object_mut) {
+        split_grid_size(entry, "grid_size", "grid_width", "grid_height");
+    }
+}
+
+// Replays are written without pretty printing since a long run holds thousands of events, returns the file name inside the replay directory
+pub fn write_replay(replay_dir: &Path, name: &str, replay: &Replay) -> Result<String> {
+    let file_name = format!("{}.json", name);
+    let json = serde_json::to_string(replay)?;
+
+    fs::create_dir_all(replay_dir)?;
+    write_atomic(&replay_dir.join(&file_name), json.as_bytes())?;
+
+    Ok(file_name)
+}
+
+// Runs are only comparable within the same mode, and for the timer mode with the same duration
+fn same_board(a: &SaveData, b: &SaveData) -> bool {
+    a.gamemode == b.gamemode && (a.gamemode != "Timer" || timer_duration(a) == timer_duration(b))
+}
+
+pub fn personal_best(save_file: &SaveFile, run: &SaveData) -> Option<i32> {
+    save_file.games_saved.iter()
+        .filter(|entry| entry.player_name == run.player_name && same_board(entry, run))
+        .map(|entry| entry.score)
+        .max()
+}
+
+pub fn compare_run(save_file: &SaveFile, run: &SaveData) -> RunComparison {
+    let better = save_file.games_saved.iter().filter(|entry| same_board(entry, run) && entry.score > run.score).count();
+
+    RunComparison { score: run.score, previous_best: personal_best(save_file, run), rank: better + 1 }
+}
+
+// Renaming a profile keeps its runs, personal bests and statistics. A player without runs, or without a scoreboard at all, has nothing to rename
+pub fn rename_player(file_name: &Path, old_name: &str, new_name: &str, warnings: &mut Vec<String>) -> Result<()> {
+    let mut save_file = read_scoreboard(file_name, warnings)?;
+    let mut renamed = false;
+
+    for entry in save_file.games_saved.iter_mut().filter(|entry| entry.player_name == old_name) {
+        entry.player_name = new_name.to_string();
+        renamed = true;
+    }
+
+    if !renamed {
+        return Ok(());
+    }
+
+    write_scoreboard(file_name, &save_file)
+}
+
+// Takes the scoreboard the caller already read, so a broken file is only reported once.
+// Only a scoreboard read_scoreboard returned may be passed in, it is empty when the file is missing or its broken contents were copied aside
+pub fn append_to_scoreboard(file_name: &Path, mut save_file: SaveFile, save_data: SaveData) -> Result<()> {
     save_file.games_saved.push(save_data);
-    write_json(file_name, &save_file)?;
+    write_scoreboard(file_name, &save_file)?;
     
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scoreboard_migration_keeps_square_grid_sizes() {
+        let mut value = json!({ "games_saved": [{ "grid_size": 9 }, {}, { "grid_width": 8, "grid_height": 5 }] });
+
+        migrate_scoreboard_v1(&mut value);
+
+        let save_file: SaveFile = serde_json::from_value(value).unwrap();
+        let sizes: Vec<_> = save_file.games_saved.iter().map(|entry| (entry.grid_width, entry.grid_height)).collect();
+
+        assert_eq!(sizes, [(9, 9), (6, 6), (8, 5)]);
+    }
+
+    #[test]
+    fn profiles_migration_keeps_square_grid_sizes() {
+        let mut value = json!({ "player_name": "Ada", "survival_grid_size": 10, "sound_fx": false });
+
+        migrate_profiles_v1(&mut value);
+        migrate_profiles_v2(&mut value);
+
+        let profiles: ProfilesFile = serde_json::from_value(value).unwrap();
+        let settings = profiles.active();
+
+        assert_eq!((settings.survival_grid_width, settings.survival_grid_height), (10, 10));
+        assert_eq!((settings.timer_grid_width, settings.timer_grid_height), (6, 6));
+        assert_eq!(settings.grid_size(Mode::Math), (10, 10));
+        assert_eq!(settings.grid_size(Mode::Zen), (10, 10));
+        assert_eq!(settings.sound_fx_volume, 0.0);
+    }
+
+    #[test]
+    fn unreadable_file_is_backed_up_once() {
+        let directory = std::env::temp_dir().join(format!("arrow_puzzle_backup_{}", std::process::id()));
+        let file_name = directory.join("scoreboard.json");
+
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(&file_name, "{ not json").unwrap();
+
+        let mut warnings = Vec::new();
+
+        for _ in 0..3 {
+            let error = read_versioned::<SaveFile>(&file_name, &SCOREBOARD_MIGRATIONS, &mut warnings).unwrap_err();
+
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+        }
+
+        let backups = fs::read_dir(&directory).unwrap().flatten().filter(|entry| entry.file_name().to_string_lossy().ends_with(".bak")).count();
+
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(backups, 1);
+        assert_eq!(warnings.len(), 1);
+    }
+
+    fn run(player_name: &str, gamemode: &str, time: f32, score: i32) -> SaveData {
+        serde_json::from_value(json!({ "player_name": player_name, "gamemode": gamemode, "time": time, "score": score })).unwrap()
+    }
+
+    #[test]
+    fn runs_are_compared_on_the_same_board() {
+        let first = run("Ada", "Timer", 30.0, 40);
+
+        assert_eq!(compare_run(&SaveFile::default(), &first).previous_best, None);
+        assert!(!compare_run(&SaveFile::default(), &first).is_record());
+
+        let games_saved = vec![
+            first,
+            run("Bob", "Timer", 30.0, 50),
+            run("Bob", "Timer", 30.0, 40),
+            run("Ada", "Timer", 120.0, 90),
+            run("Ada", "Survival", 0.0, 90)
+        ];
+        let save_file = SaveFile { games_saved, ..SaveFile::default() };
+
+        // Runs that tie are not ahead, and the 120 second Timer and Survival runs are not on the same board
+        let comparison = compare_run(&save_file, &run("Ada", "Timer", 30.0, 45));
+
+        assert_eq!(comparison.rank, 2);
+        assert_eq!(comparison.previous_best, Some(40));
+        assert!(comparison.is_record());
+
+        assert_eq!(compare_run(&save_file, &run("Ada", "Timer", 30.0, 40)).rank, 2);
+    }
+
+    #[test]
+    fn missing_scoreboard_reads_as_empty() {
+        let mut warnings = Vec::new();
+        let save_file = read_scoreboard(&std::env::temp_dir().join("arrow_puzzle_missing").join("scoreboard.json"), &mut warnings).unwrap();
+
+        assert!(save_file.games_saved.is_empty());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn broken_scoreboard_is_recovered_from_its_backup() {
+        let directory = std::env::temp_dir().join(format!("arrow_puzzle_recover_{}", std::process::id()));
+        let file_name = directory.join("scoreboard.json");
+        let backup = scoreboard_backup(&file_name);
+
+        fs::create_dir_all(&directory).unwrap();
+
+        // The second write keeps the first one as the backup
+        write_scoreboard(&file_name, &SaveFile { games_saved: vec![run("Ada", "Zen", 10.0, 5)], ..SaveFile::default() }).unwrap();
+        write_scoreboard(&file_name, &SaveFile { games_saved: vec![run("Ada", "Zen", 10.0, 5), run("Bob", "Zen", 10.0, 7)], ..SaveFile::default() }).unwrap();
+        fs::write(&file_name, "{ not json").unwrap();
+
+        let mut warnings = Vec::new();
+        let recovered = read_scoreboard(&file_name, &mut warnings).unwrap();
+        let kept_backup = fs::read(&backup).unwrap();
+
+        // A broken scoreboard is not good enough to replace the backup with
+        write_scoreboard(&file_name, &recovered).unwrap();
+
+        let backup_after_write = fs::read(&backup).unwrap();
+
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(recovered.games_saved.len(), 1);
+        assert!(warnings.iter().any(|warning| warning.starts_with("Recovered")));
+        assert_eq!(backup_after_write, kept_backup);
+    }
+
+    #[test]
+    fn unreadable_scoreboard_starts_over_once_a_copy_is_kept() {
+        let directory = std::env::temp_dir().join(format!("arrow_puzzle_start_over_{}", std::process::id()));
+        let file_name = directory.join("scoreboard.json");
+
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(&file_name, "{ not json").unwrap();
+
+        let mut warnings = Vec::new();
+        let save_file = read_scoreboard(&file_name, &mut warnings).unwrap();
+        let backups = fs::read_dir(&directory).unwrap().flatten().filter(|entry| entry.file_name().to_string_lossy().ends_with(".bak")).count();
+
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert!(save_file.games_saved.is_empty());
+        assert_eq!(backups, 1);
+    }
+
+    #[test]
+    fn renaming_without_a_scoreboard_writes_nothing() {
+        let file_name = std::env::temp_dir().join("arrow_puzzle_missing").join("scoreboard.json");
+        let mut warnings = Vec::new();
+
+        rename_player(&file_name, "", "Ada", &mut warnings).unwrap();
+
+        assert!(!file_name.exists());
+        assert!(warnings.is_empty());
+    }
+}
@@ -0,0 +1,40 @@
+use macroquad::input::utils::{register_input_subscriber, repeat_all_miniquad_input};
+use macroquad::miniquad::EventHandler;
+
+// Notices when the window goes to the background so a run can pause itself.
+// Despite their names miniquad sends the minimized and restored events whenever the window loses or gets the focus
+// (X11, Wayland, Windows, macOS and the web), so alt-tabbing away pauses a run just like minimizing does
+pub struct FocusWatcher {
+    subscriber: usize,
+    unfocused: bool
+}
+
+impl FocusWatcher {
+    pub fn new() -> FocusWatcher {
+        FocusWatcher { subscriber: register_input_subscriber(), unfocused: false }
+    }
+
+    // Must be called every frame, returns true once each time the window loses focus
+    pub fn lost_focus(&mut self) -> bool {
+        let subscriber = self.subscriber;
+        let was_unfocused = self.unfocused;
+
+        repeat_all_miniquad_input(self, subscriber);
+
+        self.unfocused && !was_unfocused
+    }
+}
+
+impl EventHandler for FocusWatcher {
+    fn update(&mut self) {}
+
+    fn draw(&mut self) {}
+
+    fn window_minimized_event(&mut self) {
+        self.unfocused = true;
+    }
+
+    fn window_restored_event(&mut self) {
+        self.unfocused = false;
+    }
+}
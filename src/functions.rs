@@ -1,12 +1,16 @@
 use macroquad::prelude::*;
 use macroquad::ui::{root_ui, widgets};
 
-use crate::audio::Audio;
-use crate::grid::*;
+use arrow_puzzle::audio::Audio;
+use crate::arrow::neon_color;
+use arrow_puzzle::grid::*;
 use crate::gamestate::*;
-use crate::file::SaveData;
+use arrow_puzzle::file::{SaveData, RunComparison};
+use arrow_puzzle::leaderboard::*;
+use arrow_puzzle::statistics::PlayerStatistics;
+use arrow_puzzle::scoring::Scoring;
 
-pub fn cell_from_mouse(grid_size: usize, cell_size: f32, offset: Vec2) -> Option<(usize, usize)> {
+pub fn cell_from_mouse(width: usize, height: usize, cell_size: f32, offset: Vec2) -> Option<(usize, usize)> {
     if !is_mouse_button_pressed(MouseButton::Left) {
         return None;
     }
@@ -20,54 +24,75 @@ pub fn cell_from_mouse(grid_size: usize, cell_size: f32, offset: Vec2) -> Option
     let x = ((mx - offset.x) / cell_size) as usize;
     let y = ((my - offset.y) / cell_size) as usize;
 
-    if x < grid_size && y < grid_size {
+    if x < width && y < height {
         return Some((x, y));
     }
     
     None
 }
 
-pub fn can_remove(grid: &Grid, x: usize, y: usize, grid_size: usize) -> bool {
-    let arrow = match &grid[y][x] {
-        Some(a) => a,
-        None => return false
-    };
+const MAX_CELL_SIZE: f32 = 80.0;
 
-    match arrow.dir {
-        Direction::Right => {
-            for nx in x+1..grid_size {
-                if grid[y][nx].is_some() {
-                    return false;
-                }
-            }
-        }
+// Largest cell size (up to the original 80px) at which the whole grid fits below the nav bar
+pub fn fit_cell_size(width: usize, height: usize, screen_width: f32, screen_height: f32, nav_bar_height: f32) -> f32 {
+    let cell_w = screen_width * 0.9 / width as f32;
+    let cell_h = (screen_height - nav_bar_height) * 0.9 / height as f32;
 
-        Direction::Left => {
-            for nx in (0..x).rev() {
-                if grid[y][nx].is_some() {
-                    return false;
-                }
-            }
-        }
+    cell_w.min(cell_h).clamp(1.0, MAX_CELL_SIZE)
+}
 
-        Direction::Down => {
-            for ny in y+1..grid_size {
-                if grid[ny][x].is_some() {
-                    return false;
-                }
-            }
-        }
+pub fn grid_offset(width: usize, height: usize, cell_size: f32, screen_width: f32, screen_height: f32, nav_bar_height: f32) -> Vec2 {
+    let grid_w = width as f32 * cell_size;
+    let grid_h = height as f32 * cell_size;
 
-        Direction::Up => {
-            for ny in (0..y).rev() {
-                if grid[ny][x].is_some() {
-                    return false;
-                }
-            }
-        }
+    let x = (screen_width - grid_w) / 2.0;
+    let y = nav_bar_height + (screen_height - nav_bar_height - grid_h) / 2.0;
+
+    vec2(x.max(0.0), y.max(nav_bar_height))
+}
+
+// Label with a "-" button on its left and a "+" button on its right, returns the step that was pressed
+pub fn draw_value_picker(text: &str, center_x: f32, y: f32) -> Option<i32> {
+    let font_size = 32.0;
+    let button_size = 32.0;
+    let half_width = 150.0;
+
+    let text_dims = measure_text(text, None, font_size as u16, 1.0);
+
+    draw_text(text, center_x - text_dims.width / 2.0, y + button_size / 2.0 + font_size / 4.0, font_size, WHITE);
+
+    if widgets::Button::new("-").position(vec2(center_x - half_width - button_size, y)).size(vec2(button_size, button_size)).ui(&mut root_ui()) {
+        return Some(-1);
+    }
+
+    if widgets::Button::new("+").position(vec2(center_x + half_width, y)).size(vec2(button_size, button_size)).ui(&mut root_ui()) {
+        return Some(1);
+    }
+
+    None
+}
+
+// Horizontal 0 - 100% slider, dragging anywhere on the bar moves the value
+pub fn draw_volume_slider(label: &str, value: &mut f32, center_x: f32, y: f32) {
+    let font_size = 28.0;
+    let bar_width = 240.0;
+    let bar_height = 16.0;
+    let bar_x = center_x - 40.0;
+
+    let (mx, my) = mouse_position();
+
+    if is_mouse_button_down(MouseButton::Left) && mx >= bar_x - 10.0 && mx <= bar_x + bar_width + 10.0 && my >= y - 8.0 && my <= y + bar_height + 8.0 {
+        *value = ((mx - bar_x) / bar_width).clamp(0.0, 1.0);
     }
 
-    true
+    let text = format!("{} {:.0}%", label, *value * 100.0);
+    let text_dims = measure_text(&text, None, font_size as u16, 1.0);
+
+    draw_text(&text, bar_x - 20.0 - text_dims.width, y + bar_height / 2.0 + font_size / 4.0, font_size, WHITE);
+
+    draw_rectangle(bar_x, y, bar_width, bar_height, DARKGRAY);
+    draw_rectangle(bar_x, y, bar_width * *value, bar_height, Color::from_rgba(180, 180, 100, 255));
+    draw_circle(bar_x + bar_width * *value, y + bar_height / 2.0, bar_height * 0.75, LIGHTGRAY);
 }
 
 pub fn draw_regenerate_button(screen_w: f32, screen_h: f32) -> Option<bool> {
@@ -86,7 +111,58 @@ pub fn draw_regenerate_button(screen_w: f32, screen_h: f32) -> Option<bool> {
     None
 }
 
-pub fn draw_game_end_screen(screen_w: f32, screen_h: f32, score: i32) -> Option<GameEndAction> {
+// Drawn through the ui so it stays on top of every button
+pub fn draw_notice(text: &str, screen_w: f32, screen_h: f32) {
+    let height = 40.0;
+    let text_dims = measure_text(text, None, 20, 1.0);
+    let width = text_dims.width + 40.0;
+    let background = Color::new(0.5, 0.0, 0.0, 0.9);
+
+    root_ui().canvas().rect(Rect::new((screen_w - width) / 2.0, screen_h - height - 10.0, width, height), background, background);
+    root_ui().label(Some(vec2((screen_w - text_dims.width) / 2.0, screen_h - height)), text);
+}
+
+// Covers the whole play area so the grid cannot be studied while paused
+pub fn draw_pause_menu(screen_w: f32, screen_h: f32, nav_bar_height: f32) -> Option<PauseAction> {
+    let button_width = 220.0;
+    let button_height = 50.0;
+    let spacing = 20.0;
+    let center_x = screen_w / 2.0;
+    let button_x = center_x - button_width / 2.0;
+
+    draw_rectangle(0.0, nav_bar_height, screen_w, screen_h - nav_bar_height, Color::new(0.1, 0.1, 0.1, 1.0));
+
+    let title_size = 48.0;
+    let title_dims = measure_text("Paused", None, title_size as u16, 1.0);
+
+    draw_text("Paused", center_x - title_dims.width / 2.0, screen_h * 0.3, title_size, LIGHTGRAY);
+
+    let actions = [
+        ("Resume", PauseAction::Resume),
+        ("Restart", PauseAction::Restart),
+        ("Settings", PauseAction::Settings),
+        ("Main Menu", PauseAction::MainMenu)
+    ];
+
+    for (i, (label, action)) in actions.into_iter().enumerate() {
+        let button_y = screen_h * 0.38 + i as f32 * (button_height + spacing);
+
+        if widgets::Button::new(label).position(vec2(button_x, button_y)).size(vec2(button_width, button_height)).ui(&mut root_ui()) {
+            return Some(action);
+        }
+    }
+
+    None
+}
+
+pub fn draw_countdown(label: &str, screen_w: f32, screen_h: f32) {
+    let font_size = 120.0;
+    let label_dims = measure_text(label, None, font_size as u16, 1.0);
+
+    draw_text(label, (screen_w - label_dims.width) / 2.0, (screen_h + label_dims.offset_y) / 2.0, font_size, Color::new(1.0, 1.0, 1.0, 0.8));
+}
+
+pub fn draw_game_end_screen(screen_w: f32, screen_h: f32, scoring: &Scoring, seed: u64, comparison: Option<&RunComparison>) -> Option<GameEndAction> {
     let button_width = 220.0;
     let button_height = 50.0;
     let spacing = 20.0;
@@ -95,15 +171,59 @@ pub fn draw_game_end_screen(screen_w: f32, screen_h: f32, score: i32) -> Option<
     draw_rectangle(0.0, 0.0, screen_w, screen_h, Color::new(0.0,0.0,0.0,0.6));
 
     // Score
-    let score_text = format!("Final Score: {}", score);
+    let score_text = format!("Final Score: {}", scoring.total());
     let score_size = 32.0;
     let score_dims = measure_text(&score_text, None, score_size as u16, 1.0);
 
-    draw_text(&score_text, center_x - score_dims.width / 2.0, screen_h * 0.4, score_size, LIGHTGRAY);
-    
+    draw_text(&score_text, center_x - score_dims.width / 2.0, screen_h * 0.2, score_size, LIGHTGRAY);
+
+    // Score breakdown
+    let breakdown_size = 24.0;
+    let breakdown = [
+        format!("Cleared: {}", scoring.base),
+        format!("Streak bonus: +{} (best streak {})", scoring.streak_bonus, scoring.best_streak),
+        format!("Grid bonus: +{}", scoring.grid_bonus),
+        format!("Fast clear bonus: +{}", scoring.fast_clear_bonus),
+        format!("Hint penalty: -{} ({} used)", scoring.hint_penalty, scoring.hints_used)
+    ];
+
+    for (i, line) in breakdown.iter().enumerate() {
+        let line_dims = measure_text(line, None, breakdown_size as u16, 1.0);
+
+        draw_text(line, center_x - line_dims.width / 2.0, screen_h * 0.2 + score_size + i as f32 * breakdown_size, breakdown_size, LIGHTGRAY);
+    }
+
+    // Seed
+    let seed_text = format!("Seed: {:016X}", seed);
+    let seed_size = 20.0;
+    let seed_dims = measure_text(&seed_text, None, seed_size as u16, 1.0);
+
+    let seed_y = screen_h * 0.2 + score_size + (breakdown.len() as f32 + 0.5) * breakdown_size;
+
+    draw_text(&seed_text, center_x - seed_dims.width / 2.0, seed_y, seed_size, GRAY);
+
+    // Personal best and rank
+    if let Some(comparison) = comparison {
+        let best_text = match comparison.previous_best {
+            Some(best) => format!("Previous best: {} ({:+})", best, comparison.score - best),
+            None => "First run on this board".to_string()
+        };
+        let rank_text = format!("Leaderboard rank: #{}", comparison.rank);
+
+        for (i, line) in [best_text, rank_text].iter().enumerate() {
+            let line_dims = measure_text(line, None, breakdown_size as u16, 1.0);
+
+            draw_text(line, center_x - line_dims.width / 2.0, seed_y + (i as f32 + 1.5) * breakdown_size, breakdown_size, LIGHTGRAY);
+        }
+
+        if comparison.is_record() {
+            draw_record_celebration(center_x, screen_h * 0.1);
+        }
+    }
+
     // Restart button
     let restart_x = center_x - button_width / 2.0;
-    let restart_y = screen_h * 0.5;
+    let restart_y = screen_h * 0.62;
 
     if widgets::Button::new("Restart").position(vec2(restart_x, restart_y)).size(vec2(button_width, button_height)).ui(&mut root_ui()) {
         return Some(GameEndAction::Restart);
@@ -119,6 +239,86 @@ pub fn draw_game_end_screen(screen_w: f32, screen_h: f32, score: i32) -> Option<
     None
 }
 
+// Totals of one player on top, their score over time in the remaining space
+pub fn draw_player_statistics(player: &PlayerStatistics, area: Rect) {
+    let font_size = 24.0;
+    let line_height = 30.0;
+
+    let modes: Vec<String> = player.games_per_mode.iter().map(|(gamemode, games)| format!("{} {}", gamemode, games)).collect();
+    let accuracy = match player.accuracy() {
+        Some(accuracy) => format!("{:.1}%", accuracy * 100.0),
+        None => "-".to_string()
+    };
+
+    let lines = [
+        format!("Games played: {} ({})", player.games(), modes.join(", ")),
+        format!("Total removed: {}", player.removed),
+        format!("Accuracy: {} ({} mistakes in {} clicks)", accuracy, player.mistakes, player.clicks),
+        format!("Average score: {:.1}", player.average_score()),
+        format!("Best score: {}", player.best_score),
+        format!("Total play time: {}", format_time(player.play_time))
+    ];
+
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(line, area.x, area.y + font_size + i as f32 * line_height, font_size, WHITE);
+    }
+
+    let chart_y = area.y + (lines.len() as f32 + 0.5) * line_height;
+
+    draw_score_chart(&player.scores, Rect::new(area.x, chart_y, area.w, area.y + area.h - chart_y));
+}
+
+// Line chart of scores in the order they were played, scaled between the lowest and highest score
+fn draw_score_chart(scores: &[i32], area: Rect) {
+    let padding = 30.0;
+    let min = scores.iter().copied().min().unwrap_or(0).min(0);
+    let max = scores.iter().copied().max().unwrap_or(0).max(min + 1);
+
+    draw_rectangle(area.x, area.y, area.w, area.h, Color::new(0.0, 0.0, 0.0, 0.4));
+    draw_rectangle_lines(area.x, area.y, area.w, area.h, 2.0, GRAY);
+
+    draw_text(max.to_string(), area.x + 6.0, area.y + 18.0, 18.0, GRAY);
+    draw_text(min.to_string(), area.x + 6.0, area.y + area.h - 6.0, 18.0, GRAY);
+
+    let point = |i: usize, score: i32| {
+        let step = if scores.len() > 1 { (area.w - padding * 2.0) / (scores.len() - 1) as f32 } else { 0.0 };
+        let height = (score - min) as f32 / (max - min) as f32;
+
+        vec2(area.x + padding + i as f32 * step, area.y + area.h - padding - height * (area.h - padding * 2.0))
+    };
+
+    for (i, pair) in scores.windows(2).enumerate() {
+        let from = point(i, pair[0]);
+        let to = point(i + 1, pair[1]);
+
+        draw_line(from.x, from.y, to.x, to.y, 2.0, Color::from_rgba(180, 180, 100, 255));
+    }
+
+    for (i, &score) in scores.iter().enumerate() {
+        let p = point(i, score);
+
+        draw_circle(p.x, p.y, 3.0, LIGHTGRAY);
+    }
+}
+
+// Pulsing "NEW RECORD!" with sparks circling around it in the neon colors
+fn draw_record_celebration(center_x: f32, y: f32) {
+    let time = get_time() as f32;
+    let text = "NEW RECORD!";
+    let size = 40.0 + (time * 6.0).sin() * 4.0;
+    let color = neon_color(NEON_COLORS[(time * 4.0) as usize % NEON_COLORS.len()]);
+    let dims = measure_text(text, None, size as u16, 1.0);
+
+    draw_text(text, center_x - dims.width / 2.0, y, size, color);
+
+    for (i, spark_color) in NEON_COLORS.iter().enumerate() {
+        let angle = time * 2.0 + i as f32 * std::f32::consts::TAU / NEON_COLORS.len() as f32;
+        let spark = vec2(center_x + angle.cos() * (dims.width / 2.0 + 30.0), y - dims.height / 2.0 + angle.sin() * 30.0);
+
+        draw_circle(spark.x, spark.y, 4.0, neon_color(*spark_color));
+    }
+}
+
 fn format_time(seconds: f32) -> String {
     let secs = seconds.max(0.0) as i32;
     let minutes = secs / 60;
@@ -127,7 +327,20 @@ fn format_time(seconds: f32) -> String {
     format!("{:02}:{:02}", minutes, seconds)
 }
 
-pub fn draw_nav_bar(score: i32, health :i32, timer: f32, screen_w: f32, nav_bar_height: f32, game_state: &mut GameState, audio: &mut Audio) {
+// Values shown in the nav bar during a run
+#[derive(Clone, Copy)]
+pub struct NavBarStats {
+    pub score: i32,
+    pub multiplier: u32,
+    pub health: i32,
+    pub timer: f32,
+    pub target: Option<(u32, u32)>,
+    pub hints_left: Option<u32>, // None in modes without hints
+    pub history: (bool, bool) // Whether undo and redo are available
+}
+
+pub fn draw_nav_bar(stats: &NavBarStats, screen_w: f32, nav_bar_height: f32, game_state: &GameState, audio: &mut Audio) -> Option<NavBarAction> {
+    let NavBarStats { score, multiplier, health, timer, target, hints_left, history } = *stats;
     let font_size = 32.0;
     let button_width = 90.0;
     let button_height = 32.0;
@@ -137,9 +350,14 @@ pub fn draw_nav_bar(score: i32, health :i32, timer: f32, screen_w: f32, nav_bar_
 
     // Score (left)
     let score_text = format!("Score: {}", score);
+    let score_dims = measure_text(&score_text, None, font_size as u16, 1.0);
     
     draw_text(&score_text, 20.0, nav_bar_height / 2.0 + font_size / 2.5, font_size, WHITE);
 
+    if multiplier > 1 {
+        draw_text(format!("x{}", multiplier), 30.0 + score_dims.width, nav_bar_height / 2.0 + font_size / 2.5, font_size, YELLOW);
+    }
+
     // Timer / Health (center)
     let center_text: String;
 
@@ -148,34 +366,82 @@ pub fn draw_nav_bar(score: i32, health :i32, timer: f32, screen_w: f32, nav_bar_
         if health <= 1 {
             center_text_color = RED;
         }
+    } else if *game_state == GameState::PlayingMath {
+        let (sum, target) = target.unwrap_or((0, 0));
+
+        center_text = format!("Health: {}  Target: {}/{}", health, sum, target);
+        if health <= 1 {
+            center_text_color = RED;
+        }
     } else if *game_state == GameState::PlayingTimer {
         if timer < 6.0 {
             center_text_color = RED;
         }
         center_text = format!("Timer: {}", format_time(timer));
+    } else if *game_state == GameState::PlayingZen {
+        center_text = format!("Zen: {}", format_time(timer));
     } else {
         center_text = "".to_string();
     }
 
     draw_text(&center_text, (screen_w / 2.0) - button_width, nav_bar_height / 2.0 + font_size / 2.5, font_size, center_text_color);
 
-    // Back button (right)
-    if !(health <= 0 || timer <= 0.0) {
-        if widgets::Button::new("Back").position(vec2(screen_w - button_width - 20.0, nav_bar_height / 2.0 - button_height / 2.0)).size(vec2(button_width, button_height)).ui(&mut root_ui()) {
+    // Pause button (right)
+    let button_y = nav_bar_height / 2.0 - button_height / 2.0;
+    let pause_x = screen_w - button_width - 20.0;
+
+    if !(health <= 0 || timer <= 0.0) && widgets::Button::new("Pause").position(vec2(pause_x, button_y)).size(vec2(button_width, button_height)).ui(&mut root_ui()) {
+        audio.play_button();
+        return Some(NavBarAction::Pause);
+    }
+
+    // Hint button (left of pause)
+    let hint_x = pause_x - button_width - 10.0;
+
+    if !(health <= 0 || timer <= 0.0) && let Some(hints_left) = hints_left && hints_left > 0 && widgets::Button::new(format!("Hint ({})", hints_left)).position(vec2(hint_x, button_y)).size(vec2(button_width, button_height)).ui(&mut root_ui()) {
+        audio.play_button();
+        return Some(NavBarAction::Hint);
+    }
+
+    // Undo / Redo buttons (left of hint, Zen only)
+    if *game_state == GameState::PlayingZen {
+        let (can_undo, can_redo) = history;
+
+        if can_redo && widgets::Button::new("Redo").position(vec2(hint_x - button_width - 10.0, button_y)).size(vec2(button_width, button_height)).ui(&mut root_ui()) {
+            audio.play_button();
+            return Some(NavBarAction::Redo);
+        }
+
+        if can_undo && widgets::Button::new("Undo").position(vec2(hint_x - (button_width + 10.0) * 2.0, button_y)).size(vec2(button_width, button_height)).ui(&mut root_ui()) {
             audio.play_button();
-            *game_state = GameState::MainMenu;
+            return Some(NavBarAction::Undo);
         }
     }
+
+    None
 }
 
-pub fn draw_scrollable_table(x: f32, y: f32, width: f32, height: f32, rows: &[SaveData], first_row: &mut usize) {
+// Rows point into the scoreboard entries, returns which header or entry was clicked
+pub fn draw_scrollable_table(area: Rect, entries: &[SaveData], rows: &[TableRow], view: &ScoreboardView, first_row: &mut usize) -> Option<TableClick> {
     const ROW_HEIGHT: f32 = 26.0;
     const HEADER_HEIGHT: f32 = 30.0;
-
+    const COLUMNS: [(&str, SortColumn, f32); 6] = [
+        ("Player", SortColumn::Player, 0.05),
+        ("Gamemode", SortColumn::Gamemode, 0.2),
+        ("Time", SortColumn::Time, 0.35),
+        ("Hints", SortColumn::Hints, 0.45),
+        ("Score", SortColumn::Score, 0.55),
+        ("Datetime", SortColumn::Datetime, 0.7)
+    ];
+
+    let Rect { x, y, w: width, h: height } = area;
     let (mx, my) = mouse_position();
 
     let visible_rows = (((height - HEADER_HEIGHT) / ROW_HEIGHT).floor() as usize).min(rows.len());
     let max_first_row = rows.len().saturating_sub(visible_rows);
+    let mut clicked = None;
+
+    *first_row = (*first_row).min(max_first_row);
 
     if mx >= x && mx <= x + width && my >= y && my <= y + height {
         let (_, wheel) = mouse_wheel();
@@ -189,35 +455,100 @@ pub fn draw_scrollable_table(x: f32, y: f32, width: f32, height: f32, rows: &[Sa
         }
     }
 
-    // Header
+    // Header, clicking a column title sorts by it
     draw_rectangle(x, y, width, HEADER_HEIGHT, DARKBLUE);
 
-    draw_text("ID", x + 10.0, y + 20.0, 20.0, WHITE);
-    draw_text("Player", x + 10.0 + (width * 0.05), y + 20.0, 20.0, WHITE);
-    draw_text("Gamemode", x + 10.0 + (width * 0.2), y + 20.0, 20.0, WHITE);
-    draw_text("Time", x + 10.0 + (width * 0.35), y + 20.0, 20.0, WHITE);
-    draw_text("Score", x + 10.0 + (width * 0.55), y + 20.0, 20.0, WHITE);
-    draw_text("Datetime", x + 10.0 + (width * 0.7), y + 20.0, 20.0, WHITE);
+    draw_text(if view.ranked() { "Rank" } else { "ID" }, x + 10.0, y + 20.0, 20.0, WHITE);
+
+    for (i, (title, column, column_x)) in COLUMNS.iter().enumerate() {
+        let start = x + width * column_x;
+        let end = COLUMNS.get(i + 1).map_or(x + width, |next| x + width * next.2);
+
+        if is_mouse_button_pressed(MouseButton::Left) && mx >= start && mx < end && my >= y && my <= y + HEADER_HEIGHT {
+            clicked = Some(TableClick::Header(*column));
+        }
+
+        let title = if view.sort == *column {
+            format!("{} {}", title, if view.descending { "v" } else { "^" })
+        } else {
+            title.to_string()
+        };
+
+        draw_text(&title, start + 10.0, y + 20.0, 20.0, if view.sort == *column { YELLOW } else { WHITE });
+    }
 
     // Draw visible rows only
     for i in 0..visible_rows {
         let row_index = *first_row + i;
         let row_y = y + HEADER_HEIGHT + i as f32 * ROW_HEIGHT;
+        let row = &rows[row_index];
+        let entry = &entries[row.index];
 
         let hovered =
             mx >= x && mx <= x + width &&
             my >= row_y && my <= row_y + ROW_HEIGHT;
 
+        if hovered && is_mouse_button_pressed(MouseButton::Left) {
+            clicked = Some(TableClick::Row(row.index));
+        }
+
         draw_rectangle(x, row_y, width, ROW_HEIGHT, if hovered { DARKGRAY } else { GRAY });
 
-        draw_text(&row_index.to_string(), x + 10.0, row_y + 18.0, 18.0, WHITE);
-        draw_text(&rows[row_index].player_name, x + 10.0 + (width * 0.05), row_y + 18.0, 18.0, WHITE);
-        draw_text(&rows[row_index].gamemode, x + 10.0 + (width * 0.2), row_y + 18.0, 18.0, WHITE);
-        draw_text(&format!("{:.2}", &rows[row_index].time), x + 10.0 + (width * 0.35), row_y + 18.0, 18.0, WHITE);
-        draw_text(&rows[row_index].score.to_string(), x + 10.0 + (width * 0.55), row_y + 18.0, 18.0, WHITE);
-        draw_text(&rows[row_index].datetime, x + 10.0 + (width * 0.7), row_y + 18.0, 18.0, WHITE);
+        draw_text(row.label.to_string(), x + 10.0, row_y + 18.0, 18.0, WHITE);
+        draw_text(&entry.player_name, x + 10.0 + (width * 0.05), row_y + 18.0, 18.0, WHITE);
+        draw_text(&entry.gamemode, x + 10.0 + (width * 0.2), row_y + 18.0, 18.0, WHITE);
+        draw_text(format!("{:.2}", entry.time), x + 10.0 + (width * 0.35), row_y + 18.0, 18.0, WHITE);
+        draw_text(entry.hints_used.to_string(), x + 10.0 + (width * 0.45), row_y + 18.0, 18.0, WHITE);
+        draw_text(entry.score.to_string(), x + 10.0 + (width * 0.55), row_y + 18.0, 18.0, WHITE);
+        draw_text(&entry.datetime, x + 10.0 + (width * 0.7), row_y + 18.0, 18.0, WHITE);
     }
 
     // Disable scissor
     set_default_camera();
+
+    clicked
+}
+
+// Play/pause, speed and back buttons above a progress bar that can be dragged to scrub through the replay
+pub fn draw_replay_controls(playing: bool, speed: f32, time: f32, duration: f32, screen_w: f32, screen_h: f32) -> Option<ReplayControl> {
+    let button_width = 120.0;
+    let button_height = 40.0;
+    let spacing = 20.0;
+    let bar_width = screen_w * 0.8;
+    let bar_height = 12.0;
+    let bar_x = (screen_w - bar_width) / 2.0;
+    let bar_y = screen_h - 40.0;
+    let buttons_x = screen_w / 2.0 - button_width * 1.5 - spacing;
+    let buttons_y = bar_y - button_height - 20.0;
+
+    let mut control = None;
+
+    if widgets::Button::new(if playing { "Pause" } else { "Play" }).position(vec2(buttons_x, buttons_y)).size(vec2(button_width, button_height)).ui(&mut root_ui()) {
+        control = Some(ReplayControl::TogglePlaying);
+    }
+
+    if widgets::Button::new(format!("Speed {}x", speed)).position(vec2(buttons_x + button_width + spacing, buttons_y)).size(vec2(button_width, button_height)).ui(&mut root_ui()) {
+        control = Some(ReplayControl::Speed);
+    }
+
+    if widgets::Button::new("Back").position(vec2(buttons_x + (button_width + spacing) * 2.0, buttons_y)).size(vec2(button_width, button_height)).ui(&mut root_ui()) {
+        control = Some(ReplayControl::Back);
+    }
+
+    let (mx, my) = mouse_position();
+
+    if is_mouse_button_down(MouseButton::Left) && mx >= bar_x - 10.0 && mx <= bar_x + bar_width + 10.0 && my >= bar_y - 8.0 && my <= bar_y + bar_height + 8.0 {
+        control = Some(ReplayControl::Seek(((mx - bar_x) / bar_width).clamp(0.0, 1.0) * duration));
+    }
+
+    let progress = if duration > 0.0 { (time / duration).clamp(0.0, 1.0) } else { 1.0 };
+    let time_text = format!("{} / {}", format_time(time), format_time(duration));
+    let time_dims = measure_text(&time_text, None, 20, 1.0);
+
+    draw_rectangle(bar_x, bar_y, bar_width, bar_height, DARKGRAY);
+    draw_rectangle(bar_x, bar_y, bar_width * progress, bar_height, Color::from_rgba(180, 180, 100, 255));
+    draw_circle(bar_x + bar_width * progress, bar_y + bar_height / 2.0, bar_height * 0.75, LIGHTGRAY);
+    draw_text(&time_text, bar_x + bar_width - time_dims.width, bar_y - 8.0, 20.0, WHITE);
+
+    control
 }
@@ -0,0 +1,476 @@
+use crate::countdown::*;
+use crate::difficulty::Difficulty;
+use crate::grid::*;
+use crate::history::*;
+use crate::math::*;
+use crate::mode::Mode;
+use crate::replay::*;
+use crate::scoring::*;
+
+// Something that happened during a call to the game, for the front-end to play sounds or show screens
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    CountdownTick,
+    CountdownGo,
+    Removed,
+    Mistake,
+    Selected,
+    Deselected,
+    Cleared(usize),
+    BoardCleared,
+    NewBoard,
+    Hint(usize, usize),
+    Undone,
+    Redone,
+    Ended
+}
+
+// One run of any mode, all the rules without anything to draw or play
+#[derive(Clone)]
+pub struct Game {
+    pub mode: Mode,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub grid_width: usize,
+    pub grid_height: usize,
+    pub timer_duration: f32,
+    pub grid: Grid,
+    pub math_board: MathBoard,
+    pub scoring: Scoring,
+    pub health: i32,
+    pub timer: f32, // Counts down in the timer mode, up everywhere else
+    pub hint: Option<(usize, usize)>,
+    pub countdown: Countdown,
+    pub replay: Replay,
+    history: History,
+    board: u32
+}
+
+impl Game {
+    pub fn new(mode: Mode, difficulty: Difficulty, grid_width: usize, grid_height: usize, timer_duration: f32) -> Game {
+        Game::with_seed(mode, random_seed(), difficulty, grid_width, grid_height, timer_duration)
+    }
+
+    // Same seed, mode and settings always deal the same boards
+    pub fn with_seed(mode: Mode, seed: u64, difficulty: Difficulty, grid_width: usize, grid_height: usize, timer_duration: f32) -> Game {
+        let is_math = mode == Mode::Math;
+        let grid = if is_math { Grid::new(grid_width, grid_height) } else { generate_grid_seeded(grid_width, grid_height, board_seed(seed, 0), Some(difficulty)) };
+        let cells = if is_math { grid_width * grid_height } else { grid.occupied().count() };
+        let health = if mode == Mode::Timer { 1 } else { 3 };
+        let timer = if mode == Mode::Timer { timer_duration } else { 0.0 };
+
+        Game {
+            math_board: MathBoard::new(grid_width, grid_height, board_seed(seed, 0)),
+            scoring: Scoring::new(cells),
+            health,
+            timer,
+            hint: None,
+            countdown: Countdown::new(),
+            replay: Replay::new(mode.name(), seed, difficulty, grid_width, grid_height),
+            history: History::new(),
+            board: 0,
+            mode,
+            seed,
+            difficulty,
+            grid_width,
+            grid_height,
+            timer_duration,
+            grid
+        }
+    }
+
+    pub fn is_math(&self) -> bool {
+        self.mode == Mode::Math
+    }
+
+    // Only the timer mode runs out of time, the other modes count their timer up
+    pub fn is_over(&self) -> bool {
+        self.health <= 0 || (self.mode == Mode::Timer && self.timer <= 0.0)
+    }
+
+    // Clicks, hints and undo only count once the countdown is done and until the run is over
+    pub fn is_playing(&self) -> bool {
+        !(self.countdown.is_running() || self.is_over())
+    }
+
+    pub fn board_cleared(&self) -> bool {
+        if self.is_math() { self.math_board.grid.is_empty() } else { self.grid.is_empty() }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    // Name the run is saved under on the scoreboard
+    pub fn gamemode(&self) -> &'static str {
+        self.mode.name()
+    }
+
+    // Time saved with the run, the timer mode stores its duration
+    pub fn run_time(&self) -> f32 {
+        if self.mode == Mode::Timer { self.timer_duration } else { self.timer }
+    }
+
+    // Advances the countdown, and once it is done the timer, the score and the replay
+    pub fn tick(&mut self, dt: f32) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+
+        match self.countdown.update(dt) {
+            Some(CountdownCue::Tick) => events.push(GameEvent::CountdownTick),
+            Some(CountdownCue::Go) => events.push(GameEvent::CountdownGo),
+            None => {}
+        }
+
+        if !self.is_playing() {
+            return events;
+        }
+
+        if self.mode == Mode::Timer {
+            self.timer -= dt;
+        } else {
+            self.timer += dt;
+        }
+
+        self.scoring.tick(dt);
+        self.replay.tick(dt);
+        self.check_end(&mut events);
+
+        events
+    }
+
+    pub fn click(&mut self, x: usize, y: usize) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+
+        if !self.is_playing() || x >= self.grid_width || y >= self.grid_height {
+            return events;
+        }
+
+        self.scoring.click();
+
+        if self.is_math() {
+            self.click_number(x, y, &mut events);
+        } else {
+            self.click_arrow(x, y, &mut events);
+        }
+
+        self.check_end(&mut events);
+
+        events
+    }
+
+    // Deals the next board once the current one is cleared
+    pub fn next_board(&mut self) -> Vec<GameEvent> {
+        if !self.is_playing() || !self.board_cleared() {
+            return Vec::new();
+        }
+
+        self.board += 1;
+
+        let seed = board_seed(self.seed, self.board);
+
+        if self.is_math() {
+            self.math_board = MathBoard::new(self.grid_width, self.grid_height, seed);
+            self.scoring.new_board(self.grid_width * self.grid_height);
+        } else {
+            self.grid = generate_grid_seeded(self.grid_width, self.grid_height, seed, Some(self.difficulty));
+            self.history.clear();
+            self.hint = None;
+            self.scoring.new_board(self.grid.occupied().count());
+        }
+
+        self.replay.record(ReplayAction::NewBoard);
+
+        vec![GameEvent::NewBoard]
+    }
+
+    // An already highlighted arrow is not charged twice
+    pub fn hint(&mut self) -> Vec<GameEvent> {
+        if !self.is_playing() || self.is_math() || self.hint.is_some() {
+            return Vec::new();
+        }
+
+        match find_hint(&self.grid) {
+            Some((x, y)) if self.scoring.use_hint() => {
+                self.hint = Some((x, y));
+                self.replay.record(ReplayAction::Hint { x, y });
+
+                vec![GameEvent::Hint(x, y)]
+            }
+
+            _ => Vec::new()
+        }
+    }
+
+    // Undo only exists in Zen so the scoreboard modes stay comparable
+    pub fn undo(&mut self) -> Vec<GameEvent> {
+        if !self.is_playing() || self.mode != Mode::Zen {
+            return Vec::new();
+        }
+
+        let Some(points) = self.history.undo(&mut self.grid) else {
+            return Vec::new();
+        };
+
+        self.scoring.undo(points);
+        self.replay.record(ReplayAction::Undo);
+        self.clear_stale_hint();
+
+        vec![GameEvent::Undone]
+    }
+
+    // Redoing the removal that cleared the board clears it again, with the same bonus it earned the first time
+    pub fn redo(&mut self) -> Vec<GameEvent> {
+        if !self.is_playing() || self.mode != Mode::Zen {
+            return Vec::new();
+        }
+
+        let Some(points) = self.history.redo(&mut self.grid) else {
+            return Vec::new();
+        };
+
+        let mut events = vec![GameEvent::Redone];
+
+        self.scoring.redo(points);
+        self.replay.record(ReplayAction::Redo);
+        self.clear_stale_hint();
+
+        if self.board_cleared() {
+            events.push(GameEvent::BoardCleared);
+        }
+
+        events
+    }
+
+    fn click_number(&mut self, x: usize, y: usize, events: &mut Vec<GameEvent>) {
+        let Some(click) = self.math_board.click(x, y) else {
+            return;
+        };
+
+        self.replay.record(ReplayAction::Click { x, y, outcome: (&click).into() });
+
+        match click {
+            MathClick::Selected => events.push(GameEvent::Selected),
+            MathClick::Deselected => events.push(GameEvent::Deselected),
+
+            MathClick::Cleared(count) => {
+                self.scoring.correct(count);
+                events.push(GameEvent::Cleared(count));
+
+                if self.math_board.grid.is_empty() {
+                    self.scoring.grid_cleared();
+                    events.push(GameEvent::BoardCleared);
+                }
+            }
+
+            MathClick::Mistake => {
+                self.scoring.mistake();
+                self.health -= 1;
+                events.push(GameEvent::Mistake);
+            }
+        }
+    }
+
+    fn click_arrow(&mut self, x: usize, y: usize, events: &mut Vec<GameEvent>) {
+        if can_remove(&self.grid, x, y) {
+            let arrow = self.grid.remove(x, y);
+            let mut points = self.scoring.correct(1);
+
+            self.replay.record(ReplayAction::Click { x, y, outcome: ClickOutcome::Removed });
+            self.clear_stale_hint();
+            events.push(GameEvent::Removed);
+
+            if self.grid.is_empty() {
+                points = points + self.scoring.grid_cleared();
+                events.push(GameEvent::BoardCleared);
+            }
+
+            if let Some(arrow) = arrow && self.mode == Mode::Zen {
+                self.history.record(x, y, arrow, points);
+            }
+        } else {
+            self.scoring.mistake();
+            self.replay.record(ReplayAction::Click { x, y, outcome: ClickOutcome::Mistake });
+            events.push(GameEvent::Mistake);
+
+            // Only survival costs health for a blocked arrow
+            if self.mode == Mode::Survival {
+                self.health -= 1;
+            }
+        }
+    }
+
+    // Hint is only shown until its arrow is gone or blocked again by an undo
+    fn clear_stale_hint(&mut self) {
+        if let Some((x, y)) = self.hint && !can_remove(&self.grid, x, y) {
+            self.hint = None;
+        }
+    }
+
+    // Calls after the end are ignored, so Ended comes exactly once per run
+    fn check_end(&mut self, events: &mut Vec<GameEvent>) {
+        if self.is_over() {
+            events.push(GameEvent::Ended);
+        }
+    }
+}
+
+#[cfg(test)]
+impl Game {
+    // A 4x4 run with a 30 second timer, played past the countdown so clicks count
+    pub(crate) fn started(mode: Mode, seed: u64, difficulty: Difficulty) -> Game {
+        let mut game = Game::with_seed(mode, seed, difficulty, 4, 4, 30.0);
+
+        game.play_countdown();
+
+        game
+    }
+
+    // Ticks until the countdown is done, returning its cues
+    pub(crate) fn play_countdown(&mut self) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+
+        while !self.is_playing() {
+            events.extend(self.tick(0.25));
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An arrow that can be removed and one that is blocked, on a board fresh from the generator
+    fn removable_and_blocked(game: &Game) -> ((usize, usize), (usize, usize)) {
+        let cells: Vec<(usize, usize)> = game.grid.occupied().map(|(x, y, _)| (x, y)).collect();
+        let removable = cells.iter().find(|&&(x, y)| can_remove(&game.grid, x, y)).unwrap();
+        let blocked = cells.iter().find(|&&(x, y)| !can_remove(&game.grid, x, y)).unwrap();
+
+        (*removable, *blocked)
+    }
+
+    fn board(seed: u64) -> Vec<(usize, usize, Arrow)> {
+        let game = Game::with_seed(Mode::Survival, seed, Difficulty::Normal, 5, 4, 30.0);
+
+        game.grid.occupied().map(|(x, y, arrow)| (x, y, arrow.clone())).collect()
+    }
+
+    #[test]
+    fn blocked_arrows_are_mistakes_that_only_cost_health_in_survival() {
+        for (mode, health_after_mistake) in [(Mode::Survival, 2), (Mode::Timer, 1), (Mode::Zen, 3)] {
+            let mut game = Game::started(mode, 7, Difficulty::Normal);
+            let (removable, blocked) = removable_and_blocked(&game);
+
+            assert_eq!(game.click(blocked.0, blocked.1), [GameEvent::Mistake]);
+            assert_eq!(game.health, health_after_mistake);
+            assert!(game.grid.get(blocked.0, blocked.1).is_some());
+
+            assert_eq!(game.click(removable.0, removable.1), [GameEvent::Removed]);
+            assert_eq!(game.health, health_after_mistake);
+            assert!(game.grid.get(removable.0, removable.1).is_none());
+        }
+    }
+
+    #[test]
+    fn overshooting_the_math_target_costs_health() {
+        let mut game = Game::started(Mode::Math, 7, Difficulty::Normal);
+        let (x, y, value) = game.math_board.grid.occupied().map(|(x, y, number)| (x, y, number.value)).find(|&(_, _, value)| value > 1).unwrap();
+
+        game.math_board.target = value - 1;
+
+        assert_eq!(game.click(x, y), [GameEvent::Mistake]);
+        assert_eq!(game.health, 2);
+
+        game.math_board.target = value;
+
+        assert_eq!(game.click(x, y), [GameEvent::Cleared(1)]);
+        assert_eq!(game.health, 2);
+    }
+
+    #[test]
+    fn timer_run_ends_exactly_once() {
+        let mut game = Game::started(Mode::Timer, 7, Difficulty::Normal);
+        let mut ended = 0;
+
+        for _ in 0..400 {
+            ended += game.tick(0.1).iter().filter(|&event| *event == GameEvent::Ended).count();
+        }
+
+        assert_eq!(ended, 1);
+        assert!(game.is_over());
+        assert!(game.click(0, 0).is_empty());
+    }
+
+    #[test]
+    fn shown_hint_is_only_charged_once() {
+        let mut game = Game::started(Mode::Survival, 7, Difficulty::Normal);
+
+        assert!(matches!(game.hint()[..], [GameEvent::Hint(_, _)]));
+
+        let charged = game.scoring.total();
+
+        assert!(game.hint().is_empty());
+        assert_eq!(game.scoring.hints_used, 1);
+        assert_eq!(game.scoring.total(), charged);
+    }
+
+    #[test]
+    fn undo_and_redo_only_work_in_zen() {
+        for mode in [Mode::Survival, Mode::Timer, Mode::Zen] {
+            let mut game = Game::started(mode, 7, Difficulty::Normal);
+            let (x, y) = find_hint(&game.grid).unwrap();
+
+            game.click(x, y);
+
+            assert_eq!(game.undo().is_empty(), mode != Mode::Zen);
+            assert_eq!(game.grid.get(x, y).is_some(), mode == Mode::Zen);
+            assert_eq!(game.redo().is_empty(), mode != Mode::Zen);
+            assert!(game.grid.get(x, y).is_none());
+        }
+    }
+
+    #[test]
+    fn replay_knows_its_mode_before_the_run_ends() {
+        for mode in Mode::ALL {
+            assert_eq!(Game::started(mode, 7, Difficulty::Normal).replay.gamemode, mode.name());
+        }
+    }
+
+    #[test]
+    fn same_seed_deals_the_same_board() {
+        assert_eq!(board(42), board(42));
+        assert_ne!(board(42), board(43));
+    }
+
+    #[test]
+    fn undo_and_redo_take_back_and_give_back_exactly_what_was_earned() {
+        let mut game = Game::started(Mode::Zen, 7, Difficulty::Normal);
+        let mut totals = vec![game.scoring.total()];
+
+        while let Some((x, y)) = find_hint(&game.grid) {
+            game.click(x, y);
+            totals.push(game.scoring.total());
+        }
+
+        assert!(game.board_cleared());
+
+        for expected in totals.iter().rev().skip(1) {
+            assert_eq!(game.undo(), [GameEvent::Undone]);
+            assert_eq!(game.scoring.total(), *expected);
+        }
+
+        for (i, expected) in totals.iter().enumerate().skip(1) {
+            let events = game.redo();
+
+            assert_eq!(events.contains(&GameEvent::BoardCleared), i == totals.len() - 1);
+            assert_eq!(game.scoring.total(), *expected);
+        }
+
+        assert!(game.board_cleared());
+    }
+}
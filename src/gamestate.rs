@@ -1,10 +1,28 @@
+use arrow_puzzle::mode::Mode;
+
 #[derive(PartialEq, Clone)]
 pub enum GameState {
     MainMenu,
     Settings,
     Scoreboard,
+    Statistics,
     PlayingSurvival,
-    PlayingTimer
+    PlayingTimer,
+    PlayingMath,
+    PlayingZen,
+    Replay
+}
+
+// Screen a run of each mode is played on
+impl From<Mode> for GameState {
+    fn from(mode: Mode) -> GameState {
+        match mode {
+            Mode::Survival => GameState::PlayingSurvival,
+            Mode::Timer => GameState::PlayingTimer,
+            Mode::Math => GameState::PlayingMath,
+            Mode::Zen => GameState::PlayingZen
+        }
+    }
 }
 
 #[derive(PartialEq)]
@@ -12,3 +30,27 @@ pub enum GameEndAction {
     Restart,
     MainMenu
 }
+
+#[derive(PartialEq)]
+pub enum PauseAction {
+    Resume,
+    Restart,
+    Settings,
+    MainMenu
+}
+
+#[derive(PartialEq)]
+pub enum ReplayControl {
+    TogglePlaying,
+    Speed,
+    Seek(f32),
+    Back
+}
+
+#[derive(PartialEq)]
+pub enum NavBarAction {
+    Pause,
+    Hint,
+    Undo,
+    Redo
+}
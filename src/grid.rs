@@ -1,7 +1,10 @@
+use rand_chacha::ChaCha8Rng;
 use rand::seq::SliceRandom;
-use macroquad::{color::Color, math::Vec2, prelude::vec2};
+use rand::SeedableRng;
+use crate::solver::solve;
+use crate::difficulty::*;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Direction {
     Up,
     Down,
@@ -9,66 +12,177 @@ pub enum Direction {
     Right
 }
 
-#[derive(Clone)]
+// Palette entry, the front-end decides what each one looks like
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NeonColor {
+    Green,
+    Magenta,
+    Cyan,
+    Yellow,
+    Orange,
+    Purple,
+    Pink,
+    Blue
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Arrow {
     pub dir: Direction,
-    pub color: Color
+    pub color: NeonColor
 }
 
-pub type Grid = Vec<Vec<Option<Arrow>>>;
+pub const NEON_COLORS: [NeonColor; 8] = [
+    NeonColor::Green,
+    NeonColor::Magenta,
+    NeonColor::Cyan,
+    NeonColor::Yellow,
+    NeonColor::Orange,
+    NeonColor::Purple,
+    NeonColor::Pink,
+    NeonColor::Blue
+];
+
+// Board of arrows by default, other modes store their own cell type
+#[derive(Clone)]
+pub struct Grid<T = Arrow> {
+    width: usize,
+    height: usize,
+    cells: Vec<Option<T>>
+}
 
-fn path_is_clear(grid: &Grid, x: usize, y: usize, dir: &Direction, grid_size: usize) -> bool {
-    match dir {
-        Direction::Right => {
-            for nx in x+1..grid_size {
-                if grid[y][nx].is_some() {
-                    return false;
-                }
-            }
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize) -> Grid<T> {
+        Grid { width, height, cells: vec![None; width * height] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.cells[y * self.width + x].as_ref()
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, cell: Option<T>) {
+        self.cells[y * self.width + x] = cell;
+    }
+
+    pub fn remove(&mut self, x: usize, y: usize) -> Option<T> {
+        self.cells[y * self.width + x].take()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter().all(|cell| cell.is_none())
+    }
+
+    // Every occupied cell in row order
+    pub fn occupied(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        self.cells.iter().enumerate().filter_map(|(i, cell)| cell.as_ref().map(|value| (i % self.width, i / self.width, value)))
+    }
+}
+
+impl Grid<Arrow> {
+    pub fn path_is_clear(&self, x: usize, y: usize, dir: &Direction) -> bool {
+        match dir {
+            Direction::Right => (x+1..self.width).all(|nx| self.get(nx, y).is_none()),
+            Direction::Left => (0..x).all(|nx| self.get(nx, y).is_none()),
+            Direction::Down => (y+1..self.height).all(|ny| self.get(x, ny).is_none()),
+            Direction::Up => (0..y).all(|ny| self.get(x, ny).is_none())
         }
+    }
+}
 
-        Direction::Left => {
-            for nx in (0..x).rev() {
-                if grid[y][nx].is_some() {
-                    return false;
-                }
-            }
+pub fn can_remove(grid: &Grid, x: usize, y: usize) -> bool {
+    match grid.get(x, y) {
+        Some(arrow) => grid.path_is_clear(x, y, &arrow.dir),
+        None => false
+    }
+}
+
+// First removable arrow in row order
+pub fn find_hint(grid: &Grid) -> Option<(usize, usize)> {
+    grid.occupied().map(|(x, y, _)| (x, y)).find(|&(x, y)| can_remove(grid, x, y))
+}
+
+pub fn random_seed() -> u64 {
+    rand::random()
+}
+
+// Seed of the n-th board generated during a run, so every board of a run can be regenerated from the run seed
+// The board index is mixed in so a later board of one run is never the first board of a neighbouring seed
+pub fn board_seed(seed: u64, board: u32) -> u64 {
+    mix(seed ^ mix(board as u64).rotate_left(32))
+}
+
+// SplitMix64 finalizer, spreads every input bit over the whole result
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+    z ^ (z >> 31)
+}
+
+pub const MIN_GRID_SIZE: usize = 4;
+pub const MAX_GRID_SIZE: usize = 12;
+pub const DEFAULT_GRID_SIZE: usize = 6;
+const DIFFICULTY_ATTEMPTS: usize = 200;
+
+// Same seed, grid dimensions and difficulty always produce the same grid, ChaCha8 gives the same stream on every platform and rand version
+pub fn generate_grid_seeded(width: usize, height: usize, seed: u64, difficulty: Option<Difficulty>) -> Grid {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut closest: Option<(f32, Grid)> = None;
+    let mut attempts = 0;
+
+    // Never hand out a grid the player could get stuck on, retrying keeps the result tied to the seed
+    loop {
+        let grid = fill_grid(width, height, &mut rng);
+        attempts += 1;
+
+        if solve(&grid).is_err() {
+            continue;
         }
 
-        Direction::Down => {
-            for ny in y+1..grid_size {
-                if grid[ny][x].is_some() {
-                    return false;
-                }
-            }
+        let Some(difficulty) = difficulty else {
+            return grid;
+        };
+
+        let rating = rate_grid(&grid);
+
+        if difficulty.contains(rating) {
+            return grid;
         }
 
-        Direction::Up => {
-            for ny in (0..y).rev() {
-                if grid[ny][x].is_some() {
-                    return false;
-                }
-            }
+        // Ties between the few possible layouts of tiny grids can make a band hard to hit, settle for the closest one after enough attempts
+        let distance = difficulty.distance(rating);
+
+        if closest.as_ref().is_none_or(|(best, _)| distance < *best) {
+            closest = Some((distance, grid));
         }
-    }
 
-    true
+        if attempts >= DIFFICULTY_ATTEMPTS && let Some((_, grid)) = closest.take() {
+            return grid;
+        }
+    }
 }
 
-pub fn generate_grid(grid_size: usize) -> Grid {
-    let mut grid = vec![vec![None; grid_size]; grid_size];
-    let mut rng = rand::rng();
+fn fill_grid(width: usize, height: usize, rng: &mut ChaCha8Rng) -> Grid {
+    let mut grid = Grid::new(width, height);
 
     // All positions shuffled
     let mut positions = Vec::new();
 
-    for y in 0..grid_size {
-        for x in 0..grid_size {
+    for y in 0..height {
+        for x in 0..width {
             positions.push((x, y));
         }
     }
 
-    positions.shuffle(&mut rng);
+    positions.shuffle(rng);
 
     for (x, y) in positions {
         let mut dirs = vec![
@@ -78,23 +192,14 @@ pub fn generate_grid(grid_size: usize) -> Grid {
             Direction::Right
         ];
 
-        let mut colors = vec![
-            Color::new(0.0, 1.0, 0.0, 1.0), // Neon Green
-            Color::new(1.0, 0.0, 1.0, 1.0), // Neon Magenta
-            Color::new(0.0, 1.0, 1.0, 1.0), // Neon Cyan
-            Color::new(1.0, 1.0, 0.0, 1.0), // Neon Yellow
-            Color::new(1.0, 0.5, 0.0, 1.0), // Neon Orange
-            Color::new(0.5, 0.0, 1.0, 1.0), // Neon Purple
-            Color::new(1.0, 0.0, 0.5, 1.0), // Neon Pink
-            Color::new(0.0, 0.5, 1.0, 1.0)  // Neon Blue
-        ];
+        let mut colors = NEON_COLORS;
 
-        dirs.shuffle(&mut rng);
-        colors.shuffle(&mut rng);
+        dirs.shuffle(rng);
+        colors.shuffle(rng);
 
         for dir in dirs {
-            if path_is_clear(&grid, x, y, &dir, grid_size) {
-                grid[y][x] = Some(Arrow { dir: dir, color: colors[0] });
+            if grid.path_is_clear(x, y, &dir) {
+                grid.set(x, y, Some(Arrow { dir, color: colors[0] }));
                 break;
             }
         }
@@ -102,16 +207,3 @@ pub fn generate_grid(grid_size: usize) -> Grid {
 
     grid
 }
-
-pub fn grid_is_empty(grid: &Grid) -> bool {
-    grid.iter().all(|row| row.iter().all(|cell| cell.is_none()))
-}
-
-pub fn grid_offset(grid_size: usize, cell_size: f32, screen_width: f32, screen_height: f32, nav_bar_height: f32) -> Vec2 {
-    let grid_px = grid_size as f32 * cell_size;
-
-    let x = (screen_width - grid_px) / 2.0;
-    let y = nav_bar_height + (screen_height - nav_bar_height - grid_px) / 2.0;
-
-    vec2(x.max(0.0), y.max(nav_bar_height))
-}
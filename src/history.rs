@@ -0,0 +1,59 @@
+use crate::grid::*;
+use crate::scoring::Points;
+
+// Removed cells in the order they were taken off the board with the points each earned, so removals can be undone and redone
+#[derive(Clone)]
+pub struct History<T = Arrow> {
+    undo: Vec<(usize, usize, T, Points)>,
+    redo: Vec<(usize, usize, T, Points)>
+}
+
+impl<T: Clone> History<T> {
+    pub fn new() -> History<T> {
+        History { undo: Vec::new(), redo: Vec::new() }
+    }
+
+    // A new removal makes the undone ones unreachable
+    pub fn record(&mut self, x: usize, y: usize, cell: T, points: Points) {
+        self.undo.push((x, y, cell, points));
+        self.redo.clear();
+    }
+
+    // Returns the points the undone removal had earned
+    pub fn undo(&mut self, grid: &mut Grid<T>) -> Option<Points> {
+        let (x, y, cell, points) = self.undo.pop()?;
+
+        grid.set(x, y, Some(cell.clone()));
+        self.redo.push((x, y, cell, points));
+
+        Some(points)
+    }
+
+    pub fn redo(&mut self, grid: &mut Grid<T>) -> Option<Points> {
+        let (x, y, cell, points) = self.redo.pop()?;
+
+        grid.remove(x, y);
+        self.undo.push((x, y, cell, points));
+
+        Some(points)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+impl<T: Clone> Default for History<T> {
+    fn default() -> History<T> {
+        History::new()
+    }
+}
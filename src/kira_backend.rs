@@ -0,0 +1,132 @@
+use kira::{AudioManager, AudioManagerSettings};
+use kira::sound::static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings};
+use kira::track::{TrackBuilder, TrackHandle};
+use kira::{Decibels, PlaybackRate, Tween};
+use std::io::Cursor;
+use std::time::Duration;
+use std::error::Error;
+
+use crate::assets::load_asset;
+use arrow_puzzle::audio::*;
+use arrow_puzzle::paths::Paths;
+
+// Falls back to null when there is no output device, what went wrong is added to the warnings for the player to see
+pub fn kira_or_null(paths: &Paths, warnings: &mut Vec<String>) -> Box<dyn AudioBackend> {
+    match KiraBackend::new(paths, warnings) {
+        Ok(backend) => Box::new(backend),
+        Err(e) => {
+            warnings.push(format!("No audio output, continuing without sound: {}", e));
+            Box::new(NullBackend)
+        }
+    }
+}
+
+// Sounds that can not be loaded stay silent
+pub struct KiraBackend {
+    manager: AudioManager,
+    music_track: TrackHandle,
+    sound_fx_track: TrackHandle,
+    button_sound_fx: Option<StaticSoundData>,
+    mistake_sound_fx: Option<StaticSoundData>,
+    success_sound_fx: Option<StaticSoundData>,
+    tick_sound_fx: Option<StaticSoundData>,
+    go_sound_fx: Option<StaticSoundData>,
+    record_sound_fx: Option<StaticSoundData>,
+    background_menu_music: Option<StaticSoundData>,
+    background_gameplay_music: Option<StaticSoundData>,
+    music_handle: Option<StaticSoundHandle>
+}
+
+impl KiraBackend {
+    pub fn new(paths: &Paths, warnings: &mut Vec<String>) -> Result<KiraBackend, Box<dyn Error>> {
+        let mut manager = AudioManager::new(AudioManagerSettings::default())?;
+        let music_track = manager.add_sub_track(TrackBuilder::new())?;
+        let sound_fx_track = manager.add_sub_track(TrackBuilder::new())?;
+
+        let button_sound_fx = load_sound(paths, "sound_fx/beep-313342.mp3", warnings);
+        let mistake_sound_fx = load_sound(paths, "sound_fx/error-mistake-sound-effect-incorrect-answer-437420.mp3", warnings);
+        let success_sound_fx = load_sound(paths, "sound_fx/tada-234709.mp3", warnings);
+
+        // Countdown cues reuse the button beep, lower for the ticks and higher for "GO"
+        let tick_sound_fx = button_sound_fx.as_ref().map(|sound| sound.playback_rate(PlaybackRate(0.8)));
+        let go_sound_fx = button_sound_fx.as_ref().map(|sound| sound.playback_rate(PlaybackRate(1.5)));
+
+        // A new personal best gets a brighter version of the tada
+        let record_sound_fx = success_sound_fx.as_ref().map(|sound| sound.playback_rate(PlaybackRate(1.25)));
+
+        let background_menu_music = load_sound(paths, "music/phatphrogstudio-cyber-attack-datastorm-rebellion-477469.mp3", warnings)
+            .map(|music| music.with_settings(StaticSoundSettings::default().loop_region(..)));
+        let background_gameplay_music = load_sound(paths, "music/phatphrogstudio-internal-fury-furyx27s-dance-477470.mp3", warnings)
+            .map(|music| music.with_settings(StaticSoundSettings::default().loop_region(..)));
+
+        Ok(KiraBackend {manager, music_track, sound_fx_track, button_sound_fx, mistake_sound_fx, success_sound_fx, tick_sound_fx, go_sound_fx, record_sound_fx, background_menu_music, background_gameplay_music, music_handle: None})
+    }
+}
+
+impl AudioBackend for KiraBackend {
+    fn set_volumes(&mut self, master_volume: f32, music_volume: f32, sound_fx_volume: f32) {
+        self.manager.main_track().set_volume(volume_to_decibels(master_volume), Tween::default());
+        self.music_track.set_volume(volume_to_decibels(music_volume), Tween::default());
+        self.sound_fx_track.set_volume(volume_to_decibels(sound_fx_volume), Tween::default());
+    }
+
+    fn play(&mut self, sound: Sound) {
+        let sound = match sound {
+            Sound::Button => &self.button_sound_fx,
+            Sound::Mistake => &self.mistake_sound_fx,
+            Sound::Success => &self.success_sound_fx,
+            Sound::Record => &self.record_sound_fx,
+            Sound::Tick => &self.tick_sound_fx,
+            Sound::Go => &self.go_sound_fx
+        };
+
+        if let Some(sound) = sound.clone() {
+            let _ = self.sound_fx_track.play(sound);
+        }
+    }
+
+    fn start_music(&mut self, music: Music, fade: f32) {
+        let music = match music {
+            Music::Menu => &self.background_menu_music,
+            Music::Gameplay => &self.background_gameplay_music
+        };
+
+        if self.music_handle.is_none() && let Some(music) = music.clone() && let Ok(handle) = self.music_track.play(music.fade_in_tween(fade_tween(fade))) {
+            self.music_handle = Some(handle);
+        }
+    }
+
+    fn stop_music(&mut self, fade: f32) {
+        if let Some(mut handle) = self.music_handle.take() {
+            handle.stop(fade_tween(fade));
+        }
+    }
+}
+
+fn load_sound(paths: &Paths, relative: &str, warnings: &mut Vec<String>) -> Option<StaticSoundData> {
+    let bytes = load_asset(paths, relative)?;
+
+    match StaticSoundData::from_cursor(Cursor::new(bytes)) {
+        Ok(sound) => Some(sound),
+        Err(e) => {
+            warnings.push(format!("Could not load {}: {}", relative, e));
+            None
+        }
+    }
+}
+
+// Volume sliders are linear in amplitude, kira tracks take decibels
+fn volume_to_decibels(volume: f32) -> Decibels {
+    if volume <= 0.0 {
+        return Decibels::SILENCE;
+    }
+
+    Decibels((20.0 * volume.log10()).max(Decibels::SILENCE.0))
+}
+
+fn fade_tween(fade: f32) -> Tween {
+    Tween {
+        duration: Duration::from_secs_f32(fade.max(0.0)),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,196 @@
+use std::cmp::Ordering;
+use chrono::NaiveDateTime;
+
+use crate::file::SaveData;
+
+pub const TOP_N: usize = 10;
+const DATETIME_FORMAT: &str = "%d-%m-%Y %H:%M:%S";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortColumn {
+    Player,
+    Gamemode,
+    Time,
+    Hints,
+    Score,
+    Datetime
+}
+
+pub enum TableClick {
+    Row(usize), // Index of the clicked entry in the scoreboard file
+    Header(SortColumn)
+}
+
+// Which gamemode the scoreboard shows the runs of
+#[derive(Clone, PartialEq)]
+pub enum Board {
+    All,
+    Mode(String),
+    Timer(u32) // Timer runs are only comparable with runs of the same duration
+}
+
+impl Board {
+    pub fn name(&self) -> String {
+        match self {
+            Board::All => "All".to_string(),
+            Board::Mode(gamemode) => gamemode.clone(),
+            Board::Timer(duration) => format!("Timer {}s", duration)
+        }
+    }
+
+    fn contains(&self, entry: &SaveData) -> bool {
+        match self {
+            Board::All => true,
+            Board::Mode(gamemode) => entry.gamemode == *gamemode,
+            Board::Timer(duration) => entry.gamemode == "Timer" && timer_duration(entry) == *duration
+        }
+    }
+}
+
+// Timer entries store the configured duration as their time
+pub fn timer_duration(entry: &SaveData) -> u32 {
+    entry.time.round() as u32
+}
+
+// Row of the scoreboard table, label is the rank on a leaderboard or the position in the file otherwise
+pub struct TableRow {
+    pub label: usize,
+    pub index: usize
+}
+
+pub struct ScoreboardView {
+    pub sort: SortColumn,
+    pub descending: bool,
+    pub board: Board,
+    pub top_n: bool, // Cuts a board down to its best runs, ranked by score
+    pub player_filter: String
+}
+
+impl ScoreboardView {
+    pub fn new() -> ScoreboardView {
+        ScoreboardView { sort: SortColumn::Score, descending: true, board: Board::All, top_n: false, player_filter: String::new() }
+    }
+
+    // Clicking the sorted column again flips the direction
+    pub fn sort_by(&mut self, column: SortColumn) {
+        if self.sort == column {
+            self.descending = !self.descending;
+        } else {
+            self.sort = column;
+            self.descending = true;
+        }
+    }
+
+    // Cycles through All and every board that has at least one entry
+    pub fn next_board(&mut self, entries: &[SaveData]) {
+        let boards = boards(entries);
+        let index = boards.iter().position(|board| *board == self.board).unwrap_or(0);
+
+        self.board = boards[(index + 1) % boards.len()].clone();
+    }
+
+    // Runs of different modes can not be ranked against each other, so All is never cut down
+    pub fn ranked(&self) -> bool {
+        self.top_n && self.board != Board::All
+    }
+
+    pub fn rows(&self, entries: &[SaveData]) -> Vec<TableRow> {
+        let filter = self.player_filter.trim().to_lowercase();
+
+        let mut rows: Vec<TableRow> = entries.iter().enumerate()
+            .filter(|(_, entry)| self.board.contains(entry))
+            .filter(|(_, entry)| filter.is_empty() || entry.player_name.to_lowercase().contains(&filter))
+            .map(|(index, _)| TableRow { label: index, index })
+            .collect();
+
+        if self.ranked() {
+            rows.sort_by(|a, b| compare(&entries[b.index], &entries[a.index], SortColumn::Score));
+            rows.truncate(TOP_N);
+
+            for (rank, row) in rows.iter_mut().enumerate() {
+                row.label = rank + 1;
+            }
+        }
+
+        rows.sort_by(|a, b| {
+            let ordering = compare(&entries[a.index], &entries[b.index], self.sort);
+
+            if self.descending { ordering.reverse() } else { ordering }
+        });
+
+        rows
+    }
+}
+
+impl Default for ScoreboardView {
+    fn default() -> ScoreboardView {
+        ScoreboardView::new()
+    }
+}
+
+fn boards(entries: &[SaveData]) -> Vec<Board> {
+    let mut boards = vec![Board::All];
+
+    for entry in entries {
+        let board = if entry.gamemode == "Timer" {
+            Board::Timer(timer_duration(entry))
+        } else {
+            Board::Mode(entry.gamemode.clone())
+        };
+
+        if !boards.contains(&board) {
+            boards.push(board);
+        }
+    }
+
+    boards
+}
+
+fn compare(a: &SaveData, b: &SaveData, column: SortColumn) -> Ordering {
+    match column {
+        SortColumn::Player => a.player_name.to_lowercase().cmp(&b.player_name.to_lowercase()),
+        SortColumn::Gamemode => a.gamemode.cmp(&b.gamemode),
+        SortColumn::Time => a.time.total_cmp(&b.time),
+        SortColumn::Hints => a.hints_used.cmp(&b.hints_used),
+        SortColumn::Score => a.score.cmp(&b.score),
+        SortColumn::Datetime => {
+            let parse = |datetime: &str| NaiveDateTime::parse_from_str(datetime, DATETIME_FORMAT).ok();
+
+            parse(&a.datetime).cmp(&parse(&b.datetime))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // Twelve Survival runs scoring 0 to 11 and a single Zen run
+    fn entries() -> Vec<SaveData> {
+        let mut entries: Vec<SaveData> = (0..12).map(|score| serde_json::from_value(json!({ "gamemode": "Survival", "score": score })).unwrap()).collect();
+
+        entries.push(serde_json::from_value(json!({ "gamemode": "Zen", "score": 50 })).unwrap());
+
+        entries
+    }
+
+    #[test]
+    fn gamemode_filter_is_separate_from_the_top_n() {
+        let entries = entries();
+        let mut view = ScoreboardView { board: Board::Mode("Survival".to_string()), ..ScoreboardView::new() };
+
+        assert_eq!(view.rows(&entries).len(), 12);
+
+        view.top_n = true;
+
+        let rows = view.rows(&entries);
+
+        assert_eq!(rows.len(), TOP_N);
+        assert_eq!((rows[0].label, entries[rows[0].index].score), (1, 11));
+
+        view.board = Board::All;
+
+        assert_eq!(view.rows(&entries).len(), 13);
+    }
+}
@@ -0,0 +1,17 @@
+// Rules, boards and save files of the game, nothing in here draws or plays sound
+pub mod grid;
+pub mod solver;
+pub mod difficulty;
+pub mod math;
+pub mod scoring;
+pub mod history;
+pub mod countdown;
+pub mod mode;
+pub mod game;
+pub mod audio;
+pub mod replay;
+pub mod playback;
+pub mod leaderboard;
+pub mod statistics;
+pub mod file;
+pub mod paths;
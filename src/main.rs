@@ -3,149 +3,413 @@ use macroquad::ui::{hash, root_ui, widgets};
 use chrono::Local;
 
 mod ui;
-mod file;
-mod audio;
-mod grid;
+mod kira_backend;
 mod arrow;
 mod functions;
+mod transition;
+mod focus;
+mod number;
+mod notice;
+mod assets;
 mod gamestate;
 
+use arrow_puzzle::file::*;
+use arrow_puzzle::grid::*;
+use arrow_puzzle::difficulty::*;
+use arrow_puzzle::mode::Mode;
+use arrow_puzzle::game::*;
+use arrow_puzzle::playback::*;
+use arrow_puzzle::replay::Replay;
+use arrow_puzzle::leaderboard::*;
+use arrow_puzzle::statistics::*;
+use arrow_puzzle::paths::Paths;
+use arrow_puzzle::audio::Audio;
+
 use ui::skin_loader::*;
-use file::*;
-use audio::Audio;
-use grid::*;
+use kira_backend::kira_or_null;
 use arrow::*;
 use functions::*;
+use transition::*;
+use focus::*;
+use number::*;
+use notice::*;
 use gamestate::*;
 
 #[macroquad::main("Arrow Puzzle")]
 async fn main() {
-    const GRID_SIZE: usize = 6;
-    const CELL_SIZE: f32 = 80.0;
     const NAV_BAR_HEIGHT: f32 = 70.0;
     const MENU_BUTTON_WIDTH: f32 = 200.0;
     const MENU_BUTTON_HEIGHT: f32 = 40.0;
+    const REPLAY_CONTROLS_HEIGHT: f32 = 110.0;
 
-    let settings: SettingsFile = match read_json("settings.json") {
-        Ok(f) => f,
-        Err(_) => { // Provide default settings
-            SettingsFile {
-                player_name: "".to_string(),
-                timer_mode_duration: 30.0,
-                sound_fx: true,
-                music: true
-            }
-        }
-    };
-    let mut audio: Audio = Audio::new(settings.sound_fx, settings.music).expect("Failed to initialize audio");
-    let mut sound_fx_input: bool = audio.sound_fx;
-    let mut music_input: bool = audio.music;
+    let mut notice = Notice::new();
+    let mut startup_warnings = Vec::new();
+
+    let paths = Paths::resolve(&mut startup_warnings);
+    paths.adopt_legacy_files(&mut startup_warnings);
+
+    let mut profiles: ProfilesFile = read_profiles(&paths.profiles(), &paths.legacy_settings(), &mut startup_warnings);
 
+    let settings: SettingsFile = profiles.active().clone();
+    let mut audio: Audio = Audio::new(|warnings| kira_or_null(&paths, warnings), settings.master_volume, settings.music_volume, settings.sound_fx_volume, &mut startup_warnings);
+
+    notice.show_all(startup_warnings);
+
+    let mut master_volume_input: f32 = audio.master_volume();
+    let mut music_volume_input: f32 = audio.music_volume();
+    let mut sound_fx_volume_input: f32 = audio.sound_fx_volume();
+
+    let mut transition = Transition::new(settings.transition_duration);
     let mut game_state = GameState::MainMenu;
     let mut previous_state = GameState::Settings;
-
-    let mut scoreboard: SaveFile = SaveFile { games_saved: Vec::new() };
-    let mut grid = generate_grid(GRID_SIZE);
-    let mut score: i32 = 0;
-    let mut health: i32 = 0;
-    let mut timer: f32 = 1.0;
+    let mut settings_return_state = GameState::MainMenu;
+    let mut focus_watcher = FocusWatcher::new();
+    let mut paused = false;
+
+    let mut scoreboard: SaveFile = SaveFile::default();
+    let mut difficulty: Difficulty = settings.difficulty;
+    let mut survival_grid_size: (usize, usize) = settings.grid_size(Mode::Survival);
+    let mut timer_grid_size: (usize, usize) = settings.grid_size(Mode::Timer);
+    let mut math_grid_size: (usize, usize) = settings.grid_size(Mode::Math);
+    let mut zen_grid_size: (usize, usize) = settings.grid_size(Mode::Zen);
+    let mut survival_grid_input: (usize, usize) = survival_grid_size;
+    let mut timer_grid_input: (usize, usize) = timer_grid_size;
+    let mut math_grid_input: (usize, usize) = math_grid_size;
+    let mut zen_grid_input: (usize, usize) = zen_grid_size;
+    let mut grid_settings_mode: Mode = Mode::Survival; // Mode whose grid size the settings screen shows
+    let mut playback: Option<Playback> = None;
+    let mut run_comparison: Option<RunComparison> = None;
     let mut timer_mode_duration: f32 = settings.timer_mode_duration;
+    let mut game = Game::new(Mode::Survival, difficulty, survival_grid_size.0, survival_grid_size.1, timer_mode_duration);
     let mut timer_input_buffer = timer_mode_duration.to_string();
     let font_size = 32.0;
     let mut first_row: usize = 0;
+    let mut scoreboard_view = ScoreboardView::new();
+    let mut statistics: Vec<PlayerStatistics> = Vec::new();
+    let mut statistics_player: usize = 0;
 
     let mut player_name = settings.player_name.clone();
     let mut player_name_input = settings.player_name.clone();
 
-    let skin_loader = SkinLoader::new();
+    let skin_loader = SkinLoader::new(&paths);
     let skin = skin_loader.await.get_skin();
     root_ui().push_skin(&skin);
 
     loop {
+        transition.begin_frame();
         clear_background(Color::new(0.1, 0.1, 0.1, 1.0));
 
         let dt = get_frame_time();
+        let lost_focus = focus_watcher.lost_focus();
         let screen_w = screen_width();
         let screen_h = screen_height();
-        let offset = grid_offset(GRID_SIZE, CELL_SIZE, screen_w, screen_h, NAV_BAR_HEIGHT);
+        let cell_size = fit_cell_size(game.grid_width, game.grid_height, screen_w, screen_h, NAV_BAR_HEIGHT);
+        let offset = grid_offset(game.grid_width, game.grid_height, cell_size, screen_w, screen_h, NAV_BAR_HEIGHT);
 
         let button_x = (screen_w - MENU_BUTTON_WIDTH) / 2.0;
         let table_x = screen_w * 0.1;
         let table_y = screen_h * 0.1;
         let table_width = screen_w - ((screen_w * 0.1) * 2.0);
-        let table_height = screen_h - ((screen_h * 0.1) * 2.0);
+        let back_button_y = screen_h * 0.8;
+        let table_height = back_button_y - table_y - 10.0; // Ends above the Back button so clicking Back does not also click a row
 
-        // Handle music when state changes
+        // Handle screen fade and music when state changes
         if game_state != previous_state {
-            audio.stop_music(); // stop whatever is playing
+            audio.stop_music(transition.duration()); // fade out whatever is playing
 
             match game_state {
-                GameState::MainMenu | GameState::Settings | GameState::Scoreboard => {
-                    audio.start_background_menu_music();
+                GameState::MainMenu | GameState::Settings | GameState::Scoreboard | GameState::Statistics | GameState::Replay => {
+                    audio.start_background_menu_music(transition.duration());
                 }
 
-                GameState::PlayingSurvival | GameState::PlayingTimer => {
-                    audio.start_background_gameplay_music();
+                GameState::PlayingSurvival | GameState::PlayingTimer | GameState::PlayingMath | GameState::PlayingZen => {
+                    audio.start_background_gameplay_music(transition.duration());
                 }
             }
 
             previous_state = game_state.clone();
         }
 
+        transition.update(dt);
+
         match game_state {
             GameState::MainMenu => {
                 // Main Menu Window
-                if widgets::Button::new("Survival").position(vec2(button_x, screen_h * 0.2)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
-                    grid = generate_grid(GRID_SIZE);
-                    score = 0;
-                    health = 3;
-                    timer = 0.0001;
-                    
+                let profile_name = if player_name.is_empty() { "(no name)" } else { &player_name };
+                let mut profile_switched = false;
+
+                if let Some(step) = draw_value_picker(&format!("Profile: {}", profile_name), screen_w / 2.0, screen_h * 0.03) {
+                    profiles.step_active(step);
+                    profile_switched = true;
+                }
+
+                if widgets::Button::new("New").position(vec2(screen_w / 2.0 + 200.0, screen_h * 0.03)).size(vec2(80.0, 32.0)).ui(&mut root_ui()) {
+                    profiles.add_profile();
+                    profile_switched = true;
+                }
+
+                // Load everything the new profile configures and remember it as the active one
+                if profile_switched {
+                    let settings = profiles.active().clone();
+
+                    player_name = settings.player_name.clone();
+                    player_name_input = settings.player_name.clone();
+                    timer_mode_duration = settings.timer_mode_duration;
+                    timer_input_buffer = timer_mode_duration.to_string();
+                    difficulty = settings.difficulty;
+                    survival_grid_size = settings.grid_size(Mode::Survival);
+                    timer_grid_size = settings.grid_size(Mode::Timer);
+                    math_grid_size = settings.grid_size(Mode::Math);
+                    zen_grid_size = settings.grid_size(Mode::Zen);
+                    survival_grid_input = survival_grid_size;
+                    timer_grid_input = timer_grid_size;
+                    math_grid_input = math_grid_size;
+                    zen_grid_input = zen_grid_size;
+                    transition = Transition::new(settings.transition_duration);
+
+                    audio.set_volumes(settings.master_volume, settings.music_volume, settings.sound_fx_volume);
+                    master_volume_input = audio.master_volume();
+                    music_volume_input = audio.music_volume();
+                    sound_fx_volume_input = audio.sound_fx_volume();
+
+                    match write_json(&paths.profiles(), &profiles) {
+                        Ok(_) => { println!("Profiles saved") },
+                        Err(e) => { notice.show(format!("Could not save profiles: {}", e)) }
+                    };
+
+                    audio.play_button();
+                }
+
+                if widgets::Button::new("Survival").position(vec2(button_x, screen_h * 0.12)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                    game = Game::new(Mode::Survival, difficulty, survival_grid_size.0, survival_grid_size.1, timer_mode_duration);
+
                     audio.play_button();
                     game_state = GameState::PlayingSurvival;
                 }
 
-                if widgets::Button::new("Timer").position(vec2(button_x, screen_h * 0.3)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
-                    grid = generate_grid(GRID_SIZE);
-                    score = 0;
-                    health = 1;
-                    timer = timer_mode_duration;
+                if widgets::Button::new("Timer").position(vec2(button_x, screen_h * 0.21)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                    game = Game::new(Mode::Timer, difficulty, timer_grid_size.0, timer_grid_size.1, timer_mode_duration);
 
                     audio.play_button();
                     game_state = GameState::PlayingTimer;
                 }
 
-                if widgets::Button::new("Scoreboard").position(vec2(button_x, screen_h * 0.4)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
-                    first_row = 0;
-                    scoreboard = match read_json("scoreboard.json") {
-                        Ok(f) => f,
-                        Err(_) => { SaveFile { games_saved: Vec::new() } }
+                if widgets::Button::new("Math").position(vec2(button_x, screen_h * 0.30)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                    game = Game::new(Mode::Math, difficulty, math_grid_size.0, math_grid_size.1, timer_mode_duration);
+
+                    audio.play_button();
+                    game_state = GameState::PlayingMath;
+                }
+
+                if widgets::Button::new("Zen").position(vec2(button_x, screen_h * 0.39)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                    game = Game::new(Mode::Zen, difficulty, zen_grid_size.0, zen_grid_size.1, timer_mode_duration);
+
+                    audio.play_button();
+                    game_state = GameState::PlayingZen;
+                }
+
+                if widgets::Button::new(format!("Difficulty - {}", difficulty.name())).position(vec2(button_x, screen_h * 0.48)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                    difficulty = difficulty.next();
+
+                    profiles.set_active(SettingsFile {
+                        player_name: player_name.clone(),
+                        timer_mode_duration,
+                        master_volume: audio.master_volume(),
+                        music_volume: audio.music_volume(),
+                        sound_fx_volume: audio.sound_fx_volume(),
+                        difficulty,
+                        survival_grid_width: survival_grid_size.0,
+                        survival_grid_height: survival_grid_size.1,
+                        timer_grid_width: timer_grid_size.0,
+                        timer_grid_height: timer_grid_size.1,
+                        math_grid_width: math_grid_size.0,
+                        math_grid_height: math_grid_size.1,
+                        zen_grid_width: zen_grid_size.0,
+                        zen_grid_height: zen_grid_size.1,
+                        transition_duration: transition.duration()});
+
+                    match write_json(&paths.profiles(), &profiles) {
+                        Ok(_) => { println!("Settings saved") },
+                        Err(e) => { notice.show(format!("Could not save settings: {}", e)) }
                     };
+
+                    audio.play_button();
+                }
+
+                if widgets::Button::new("Scoreboard").position(vec2(button_x, screen_h * 0.57)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                    first_row = 0;
+                    scoreboard = load_scoreboard(&paths, &mut notice).unwrap_or_default();
                     
                     audio.play_button();
                     game_state = GameState::Scoreboard;
                 }
 
-                if widgets::Button::new("Settings").position(vec2(button_x, screen_h * 0.5)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                if widgets::Button::new("Statistics").position(vec2(button_x, screen_h * 0.66)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                    statistics = player_statistics(&load_scoreboard(&paths, &mut notice).unwrap_or_default().games_saved);
+
+                    // Start on the current player when they have saved runs
+                    statistics_player = statistics.iter().position(|player| player.player_name == player_name).unwrap_or(0);
+
+                    audio.play_button();
+                    game_state = GameState::Statistics;
+                }
+
+                if widgets::Button::new("Settings").position(vec2(button_x, screen_h * 0.75)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                    settings_return_state = GameState::MainMenu;
+
                     audio.play_button();
                     game_state = GameState::Settings;
                 }
 
-                if widgets::Button::new("Quit").position(vec2(button_x, screen_h * 0.6)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                if widgets::Button::new("Quit").position(vec2(button_x, screen_h * 0.84)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
                     std::process::exit(0);
                 }
             }
 
             GameState::Scoreboard => {
                 // Scoreboard Window
-                draw_scrollable_table(table_x, table_y, table_width, table_height, &scoreboard.games_saved, &mut first_row);
+                if widgets::Button::new(format!("Mode: {}", scoreboard_view.board.name())).position(vec2(table_x, screen_h * 0.02)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                    scoreboard_view.next_board(&scoreboard.games_saved);
+                    first_row = 0;
 
-                if widgets::Button::new("Back").position(vec2(button_x, screen_h * 0.8)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                    audio.play_button();
+                }
+
+                draw_text("Player", table_x + MENU_BUTTON_WIDTH + 20.0, screen_h * 0.02 + MENU_BUTTON_HEIGHT / 2.0 + font_size / 4.0, font_size, WHITE);
+
+                widgets::InputText::new(hash!("player_filter_input"))
+                    .position(vec2(table_x + MENU_BUTTON_WIDTH + 110.0, screen_h * 0.02 + 4.0))
+                    .size(vec2(200.0, 32.0))
+                    .ui(&mut root_ui(), &mut scoreboard_view.player_filter);
+
+                // Listing every run of a mode and ranking its best ones are separate choices
+                if scoreboard_view.board != Board::All && widgets::Button::new(format!("Top {}: {}", TOP_N, if scoreboard_view.top_n { "On" } else { "Off" })).position(vec2(table_x, back_button_y)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                    scoreboard_view.top_n = !scoreboard_view.top_n;
+                    first_row = 0;
+
+                    audio.play_button();
+                }
+
+                let rows = scoreboard_view.rows(&scoreboard.games_saved);
+
+                match draw_scrollable_table(Rect::new(table_x, table_y, table_width, table_height), &scoreboard.games_saved, &rows, &scoreboard_view, &mut first_row) {
+                    Some(TableClick::Header(column)) => {
+                        scoreboard_view.sort_by(column);
+                        audio.play_button();
+                    }
+
+                    Some(TableClick::Row(index)) => {
+                        let entry = &scoreboard.games_saved[index];
+
+                        // Entries saved before replays existed have nothing to play back
+                        match (&entry.replay, Mode::from_name(&entry.gamemode)) {
+                            (Some(file_name), Some(mode)) => match read_json::<Replay>(&paths.replay(file_name)) {
+                                Ok(loaded) => {
+                                    playback = Some(Playback::new(loaded, mode));
+
+                                    audio.play_button();
+                                    game_state = GameState::Replay;
+                                }
+
+                                Err(e) => notice.show(format!("Could not load replay: {}", e))
+                            },
+
+                            _ => notice.show("No replay was saved for this run".to_string())
+                        }
+                    }
+
+                    None => {}
+                }
+
+                if widgets::Button::new("Back").position(vec2(button_x, back_button_y)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
                     audio.play_button();
                     game_state = GameState::MainMenu;
                 }
             }
 
+            GameState::Statistics => {
+                // Statistics Window
+                if let Some(player) = statistics.get(statistics_player) {
+                    let name = if player.player_name.is_empty() { "(no name)" } else { &player.player_name };
+
+                    if let Some(step) = draw_value_picker(&format!("Player: {}", name), screen_w / 2.0, screen_h * 0.05) {
+                        statistics_player = (statistics_player as i32 + step).rem_euclid(statistics.len() as i32) as usize;
+                        audio.play_button();
+                    }
+
+                    draw_player_statistics(player, Rect::new(table_x, screen_h * 0.14, table_width, screen_h * 0.62));
+                } else {
+                    let text = "No runs saved yet";
+                    let text_dims = measure_text(text, None, font_size as u16, 1.0);
+
+                    draw_text(text, (screen_w - text_dims.width) / 2.0, screen_h * 0.4, font_size, WHITE);
+                }
+
+                if widgets::Button::new("Back").position(vec2(button_x, screen_h * 0.85)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
+                    audio.play_button();
+                    game_state = GameState::MainMenu;
+                }
+            }
+
+            GameState::Replay => {
+                // Replay Window
+                if let Some(playback) = playback.as_mut() {
+                    if is_key_pressed(KeyCode::Space) {
+                        playback.toggle_playing();
+                    }
+
+                    playback.update(dt);
+
+                    let (width, height) = (playback.replay.grid_width, playback.replay.grid_height);
+                    let replay_cell_size = fit_cell_size(width, height, screen_w, screen_h - REPLAY_CONTROLS_HEIGHT, NAV_BAR_HEIGHT);
+                    let replay_offset = grid_offset(width, height, replay_cell_size, screen_w, screen_h - REPLAY_CONTROLS_HEIGHT, NAV_BAR_HEIGHT);
+
+                    let target = if playback.game.is_math() {
+                        draw_number_grid(&playback.game.math_board, replay_cell_size, replay_offset);
+                        Some((playback.game.math_board.selected_sum(), playback.game.math_board.target))
+                    } else {
+                        draw_arrow_grid(&playback.game.grid, replay_cell_size, replay_offset, playback.game.hint);
+                        None
+                    };
+
+                    let stats = NavBarStats { score: playback.game.scoring.total(), multiplier: playback.game.scoring.multiplier(), health: playback.game.health, timer: playback.timer(), target, hints_left: None, history: (false, false) };
+
+                    // The nav bar pause button doubles as play / pause
+                    if draw_nav_bar(&stats, screen_w, NAV_BAR_HEIGHT, &GameState::from(playback.mode), &mut audio) == Some(NavBarAction::Pause) {
+                        playback.toggle_playing();
+                    }
+
+                    match draw_replay_controls(playback.playing, playback.speed, playback.time, playback.replay.duration, screen_w, screen_h) {
+                        Some(ReplayControl::TogglePlaying) => {
+                            audio.play_button();
+                            playback.toggle_playing();
+                        }
+
+                        Some(ReplayControl::Speed) => {
+                            audio.play_button();
+                            playback.next_speed();
+                        }
+
+                        Some(ReplayControl::Seek(time)) => {
+                            playback.seek(time);
+                        }
+
+                        Some(ReplayControl::Back) => {
+                            audio.play_button();
+                            game_state = GameState::Scoreboard;
+                        }
+
+                        None => {}
+                    }
+                } else {
+                    game_state = GameState::Scoreboard;
+                }
+
+                if is_key_pressed(KeyCode::Escape) {
+                    game_state = GameState::Scoreboard;
+                }
+            }
+
             GameState::Settings => {
                 // Settings Window
                 draw_text("Player Name", (screen_w / 2.0) - 80.0, (screen_h * 0.1) + font_size / 2.5, font_size, WHITE);
@@ -155,23 +419,42 @@ async fn main() {
                     .size(vec2(200.0, 32.0))
                     .ui(&mut root_ui(), &mut player_name_input);
 
-                draw_text("Timer Duration (min: 5 sec.)", (screen_w / 2.0) - 180.0, (screen_h * 0.3) + font_size / 2.5, font_size, WHITE);
+                draw_text("Timer Duration (min: 5 sec.)", (screen_w / 2.0) - 180.0, (screen_h * 0.25) + font_size / 2.5, font_size, WHITE);
 
                 widgets::InputText::new(hash!("timer_input"))
-                    .position(vec2(screen_w / 2.0 - 100.0, screen_h * 0.3))
+                    .position(vec2(screen_w / 2.0 - 100.0, screen_h * 0.25))
                     .size(vec2(200.0, 32.0))
                     .ui(&mut root_ui(), &mut timer_input_buffer);
 
-                if widgets::Button::new(format!("Sound FX - {}", if sound_fx_input { "ON" } else { "OFF" })).position(vec2(button_x, screen_h * 0.5)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
-                    sound_fx_input = !sound_fx_input;
+                // Every mode keeps its own grid size, one mode is shown at a time
+                if let Some(step) = draw_value_picker(&format!("Grid: {}", grid_settings_mode.name()), screen_w / 2.0, screen_h * 0.33) {
+                    let index = Mode::ALL.iter().position(|&mode| mode == grid_settings_mode).unwrap_or(0) as i32;
+
+                    grid_settings_mode = Mode::ALL[(index + step).rem_euclid(Mode::ALL.len() as i32) as usize];
+                    audio.play_button();
+                }
+
+                let grid_input = match grid_settings_mode {
+                    Mode::Timer => &mut timer_grid_input,
+                    Mode::Math => &mut math_grid_input,
+                    Mode::Zen => &mut zen_grid_input,
+                    Mode::Survival => &mut survival_grid_input
+                };
+
+                if let Some(step) = draw_value_picker(&format!("Width: {}", grid_input.0), screen_w / 2.0, screen_h * 0.39) {
+                    grid_input.0 = grid_input.0.saturating_add_signed(step as isize).clamp(MIN_GRID_SIZE, MAX_GRID_SIZE);
                     audio.play_button();
                 }
 
-                if widgets::Button::new(format!("Music - {}", if music_input { "ON" } else { "OFF" })).position(vec2(button_x, screen_h * 0.6)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
-                    music_input = !music_input;
+                if let Some(step) = draw_value_picker(&format!("Height: {}", grid_input.1), screen_w / 2.0, screen_h * 0.45) {
+                    grid_input.1 = grid_input.1.saturating_add_signed(step as isize).clamp(MIN_GRID_SIZE, MAX_GRID_SIZE);
                     audio.play_button();
                 }
 
+                draw_volume_slider("Master", &mut master_volume_input, screen_w / 2.0, screen_h * 0.52);
+                draw_volume_slider("Music", &mut music_volume_input, screen_w / 2.0, screen_h * 0.58);
+                draw_volume_slider("Sound FX", &mut sound_fx_volume_input, screen_w / 2.0, screen_h * 0.64);
+
                 if widgets::Button::new("Apply").position(vec2((screen_w * 0.25) - (MENU_BUTTON_WIDTH / 2.0), screen_h * 0.8)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
                     if let Ok(value) = timer_input_buffer.parse::<f32>() {
                         timer_mode_duration = if value < 5.0 { 5.0 } else { value }
@@ -180,125 +463,277 @@ async fn main() {
                     timer_mode_duration = (timer_mode_duration / 5.0).round() * 5.0;
                     timer_input_buffer = format!("{:.0}", timer_mode_duration);
 
-                    audio.sound_fx = sound_fx_input;
-                    audio.music = music_input;
+                    audio.set_volumes(master_volume_input, music_volume_input, sound_fx_volume_input);
+                    survival_grid_size = survival_grid_input;
+                    timer_grid_size = timer_grid_input;
+                    math_grid_size = math_grid_input;
+                    zen_grid_size = zen_grid_input;
+
+                    // Two profiles can not share a name, their runs would be mixed up on the scoreboard
+                    if profiles.name_taken(&player_name_input) {
+                        notice.show(format!("Profile name {} is already taken", player_name_input));
+                        player_name_input = player_name.clone();
+                    }
+
+                    if player_name_input != player_name {
+                        let mut file_warnings = Vec::new();
+
+                        if let Err(e) = rename_player(&paths.scoreboard(), &player_name, &player_name_input, &mut file_warnings) {
+                            file_warnings.push(format!("Could not rename runs on the scoreboard: {}", e));
+                        }
+
+                        notice.show_all(file_warnings);
+                    }
+
                     player_name = player_name_input.clone();
 
-                    match write_json("settings.json", &SettingsFile {
-                        player_name: player_name_input.clone(),
-                        timer_mode_duration: timer_mode_duration,
-                        sound_fx: audio.sound_fx,
-                        music: audio.music}) {
-                            Ok(_) => { println!("Settings saved") },
-                            Err(e) => { println!("{:?}", e) }
+                    profiles.set_active(SettingsFile {
+                        player_name: player_name.clone(),
+                        timer_mode_duration,
+                        master_volume: audio.master_volume(),
+                        music_volume: audio.music_volume(),
+                        sound_fx_volume: audio.sound_fx_volume(),
+                        difficulty,
+                        survival_grid_width: survival_grid_size.0,
+                        survival_grid_height: survival_grid_size.1,
+                        timer_grid_width: timer_grid_size.0,
+                        timer_grid_height: timer_grid_size.1,
+                        math_grid_width: math_grid_size.0,
+                        math_grid_height: math_grid_size.1,
+                        zen_grid_width: zen_grid_size.0,
+                        zen_grid_height: zen_grid_size.1,
+                        transition_duration: transition.duration()});
+
+                    match write_json(&paths.profiles(), &profiles) {
+                        Ok(_) => { println!("Settings saved") },
+                        Err(e) => { notice.show(format!("Could not save settings: {}", e)) }
                     };
                     
                     audio.play_button();
-                    game_state = GameState::MainMenu;
+                    game_state = settings_return_state.clone();
                 }
 
                 if widgets::Button::new("Back").position(vec2((screen_w * 0.75) - (MENU_BUTTON_WIDTH / 2.0), screen_h * 0.8)).size(vec2(MENU_BUTTON_WIDTH, MENU_BUTTON_HEIGHT)).ui(&mut root_ui()) {
                     timer_input_buffer = format!("{:.0}", timer_mode_duration);
-                    sound_fx_input = audio.sound_fx;
-                    music_input = audio.music;
+                    master_volume_input = audio.master_volume();
+                    music_volume_input = audio.music_volume();
+                    sound_fx_volume_input = audio.sound_fx_volume();
                     player_name_input = player_name.clone();
+                    survival_grid_input = survival_grid_size;
+                    timer_grid_input = timer_grid_size;
+                    math_grid_input = math_grid_size;
+                    zen_grid_input = zen_grid_size;
                     
                     audio.play_button();
-                    game_state = GameState::MainMenu;
+                    game_state = settings_return_state.clone();
                 }
             }
 
-            GameState::PlayingSurvival | GameState::PlayingTimer => {
+            GameState::PlayingSurvival | GameState::PlayingTimer | GameState::PlayingMath | GameState::PlayingZen => {
                 // Gameplay Window
-                if !(health <= 0 || timer <= 0.0) {
-                    if grid_is_empty(&grid) {
+                let mut restart_run = false;
+                let mut events: Vec<GameEvent> = Vec::new();
+
+                if !game.is_over() && (is_key_pressed(KeyCode::Escape) || (lost_focus && !paused)) {
+                    paused = !paused;
+                }
+
+                if !paused && game.is_playing() {
+                    if is_key_pressed(KeyCode::H) {
+                        events.extend(game.hint());
+                    }
+
+                    if game.board_cleared() {
                         if draw_regenerate_button(screen_w, screen_h) == Some(true) {
-                            grid = generate_grid(GRID_SIZE);
+                            events.extend(game.next_board());
                         }
-                    } else {
-                        if let Some((x, y)) = cell_from_mouse(GRID_SIZE, CELL_SIZE, offset) {
-                            if can_remove(&grid, x, y, GRID_SIZE) {
-                                grid[y][x] = None;
-                                score += 1;
-                            } else if game_state == GameState::PlayingTimer {
-                                audio.play_mistake();
-                            } else {
-                                health -= 1;
-                                audio.play_mistake();
-                            }
-                        }
-                    }
-                    
-                    if game_state == GameState::PlayingTimer {
-                        timer -= dt;
-                    } else if game_state == GameState::PlayingSurvival {
-                        timer += dt;
+                    } else if let Some((x, y)) = cell_from_mouse(game.grid_width, game.grid_height, cell_size, offset) {
+                        events.extend(game.click(x, y));
                     }
 
-                    if health <= 0 || timer <= 0.0 {
-                        audio.play_success();
+                    let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+
+                    if ctrl && is_key_pressed(KeyCode::Z) {
+                        events.extend(game.undo());
+                    } else if ctrl && is_key_pressed(KeyCode::Y) {
+                        events.extend(game.redo());
                     }
                 }
-                
-                draw_arrow_grid(&grid, GRID_SIZE, CELL_SIZE, offset);
-                draw_nav_bar(score, health, timer, screen_w, NAV_BAR_HEIGHT, &mut game_state, &mut audio);
 
-                if health <= 0 || timer <= 0.0 {
-                    if let Some(action) = draw_game_end_screen(screen_w, screen_h, score) {
-                        let gamemode: String;
-                        let time: f32;
+                if !paused {
+                    events.extend(game.tick(dt));
+                }
 
-                        match game_state {
-                            GameState::PlayingSurvival => {
-                                gamemode = "Survival".to_string();
-                                time = timer; // Stopwatch
-                            }
+                if paused {
+                    // Grid stays hidden behind the pause menu
+                } else if game.is_math() {
+                    draw_number_grid(&game.math_board, cell_size, offset);
+                } else {
+                    draw_arrow_grid(&game.grid, cell_size, offset, game.hint);
+                }
 
-                            GameState::PlayingTimer => {
-                                gamemode = "Timer".to_string();
-                                time = timer_mode_duration; // Timer duration
-                            }
+                let target = if game.is_math() {
+                    Some((game.math_board.selected_sum(), game.math_board.target))
+                } else {
+                    None
+                };
+
+                let hints_left = if game.is_math() {
+                    None
+                } else {
+                    Some(game.scoring.hints_left())
+                };
+
+                let stats = NavBarStats { score: game.scoring.total(), multiplier: game.scoring.multiplier(), health: game.health, timer: game.timer, target, hints_left, history: (game.can_undo(), game.can_redo()) };
 
-                            _ => {
-                                gamemode = "Unknown".to_string();
-                                time = 0.0;
+                match draw_nav_bar(&stats, screen_w, NAV_BAR_HEIGHT, &game_state, &mut audio) {
+                    Some(NavBarAction::Pause) => {
+                        paused = true;
+                    }
+
+                    Some(NavBarAction::Hint) if !paused => {
+                        events.extend(game.hint());
+                    }
+
+                    Some(NavBarAction::Undo) if !paused => {
+                        events.extend(game.undo());
+                    }
+
+                    Some(NavBarAction::Redo) if !paused => {
+                        events.extend(game.redo());
+                    }
+
+                    _ => {}
+                }
+
+                for event in events {
+                    audio.play_event(&event);
+
+                    // Run just ended, save it right away so the end screen can show how it compares
+                    if event == GameEvent::Ended {
+                        let replay_file = match write_replay(&paths.replays(), &format!("{}-{:016X}", Local::now().format("%Y%m%d-%H%M%S"), game.seed), &game.replay) {
+                            Ok(file_name) => Some(file_name),
+                            Err(e) => {
+                                notice.show(format!("Could not save replay: {}", e));
+                                None
                             }
                         };
-                        
-                        match append_to_scoreboard("scoreboard.json", SaveData {
+
+                        let run = SaveData {
                             player_name: player_name.clone(),
-                            gamemode,
-                            time,
-                            score,
-                            datetime: Local::now().format("%d-%m-%Y %H:%M:%S").to_string() }) {
-                                Ok(_) => println!("Score saved"),
-                                Err(e) => println!("{:?}", e)
+                            gamemode: game.gamemode().to_string(),
+                            time: game.run_time(),
+                            score: game.scoring.total(),
+                            datetime: Local::now().format("%d-%m-%Y %H:%M:%S").to_string(),
+                            seed: game.seed,
+                            difficulty: game.difficulty,
+                            grid_width: game.grid_width,
+                            grid_height: game.grid_height,
+                            hints_used: game.scoring.hints_used,
+                            replay: replay_file,
+                            removed: game.scoring.base.max(0) as u32,
+                            clicks: game.scoring.clicks,
+                            mistakes: game.scoring.mistakes };
+
+                        // Compare against the runs saved before this one is added
+                        let previous_runs = load_scoreboard(&paths, &mut notice);
+                        let comparison = compare_run(previous_runs.as_ref().unwrap_or(&SaveFile::default()), &run);
+
+                        if comparison.is_record() {
+                            audio.play_record();
+                        } else {
+                            audio.play_success();
                         }
 
-                        match action {
-                            GameEndAction::Restart => {
-                                grid = generate_grid(GRID_SIZE);
-                                score = 0;
-                                health = 3;
-                                timer = if game_state == GameState::PlayingTimer {
-                                    timer_mode_duration
-                                } else {
-                                    0.0001
-                                };
-
-                                audio.play_button();
-                            }
+                        run_comparison = Some(comparison);
 
-                            GameEndAction::MainMenu => {
-                                audio.play_button();
-                                game_state = GameState::MainMenu;
-                            }
+                        // Saving over a scoreboard that could neither be read nor copied aside would lose every run in it
+                        match previous_runs.map(|previous_runs| append_to_scoreboard(&paths.scoreboard(), previous_runs, run)) {
+                            Some(Ok(_)) => println!("Score saved"),
+                            Some(Err(e)) => notice.show(format!("Could not save score: {}", e)),
+                            None => notice.show("Score not saved, the scoreboard could not be read".to_string())
                         }
                     }
                 }
+
+                if paused {
+                    match draw_pause_menu(screen_w, screen_h, NAV_BAR_HEIGHT) {
+                        Some(PauseAction::Resume) => {
+                            audio.play_button();
+                            paused = false;
+                        }
+
+                        Some(PauseAction::Restart) => {
+                            restart_run = true;
+                        }
+
+                        Some(PauseAction::Settings) => {
+                            settings_return_state = game_state.clone();
+
+                            audio.play_button();
+                            game_state = GameState::Settings;
+                        }
+
+                        Some(PauseAction::MainMenu) => {
+                            paused = false;
+
+                            audio.play_button();
+                            game_state = GameState::MainMenu;
+                        }
+
+                        None => {}
+                    }
+                } else if let Some(label) = game.countdown.label() {
+                    draw_countdown(&label, screen_w, screen_h);
+                }
+
+                if game.is_over() && let Some(action) = draw_game_end_screen(screen_w, screen_h, &game.scoring, game.seed, run_comparison.as_ref()) {
+                    match action {
+                        GameEndAction::Restart => {
+                            restart_run = true;
+                        }
+
+                        GameEndAction::MainMenu => {
+                            audio.play_button();
+                            game_state = GameState::MainMenu;
+                        }
+                    }
+                }
+
+                // Same mode and board size with a new seed, settings changed from the pause menu apply
+                if restart_run {
+                    game = Game::new(game.mode, difficulty, game.grid_width, game.grid_height, timer_mode_duration);
+                    paused = false;
+
+                    audio.play_button();
+                }
             }
         }
 
+        notice.update(dt);
+
+        if let Some(text) = notice.text() {
+            draw_notice(text, screen_w, screen_h);
+        }
+
+        transition.draw(game_state != previous_state);
+
         next_frame().await;
     }
-}
\ No newline at end of file
+}
+
+// Shows why the scoreboard could not be read instead of silently showing an empty one, None when it must not be written over
+fn load_scoreboard(paths: &Paths, notice: &mut Notice) -> Option<SaveFile> {
+    let mut file_warnings = Vec::new();
+    let scoreboard = match read_scoreboard(&paths.scoreboard(), &mut file_warnings) {
+        Ok(scoreboard) => Some(scoreboard),
+        Err(e) => {
+            file_warnings.push(format!("Could not read the scoreboard: {}", e));
+            None
+        }
+    };
+
+    notice.show_all(file_warnings);
+
+    scoreboard
+}
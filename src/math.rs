@@ -0,0 +1,98 @@
+use rand_chacha::ChaCha8Rng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::grid::*;
+
+const MAX_NUMBER: u32 = 9;
+const MAX_TARGET_TERMS: usize = 4;
+
+#[derive(Clone)]
+pub struct Number {
+    pub value: u32,
+    pub color: NeonColor
+}
+
+#[derive(PartialEq)]
+pub enum MathClick {
+    Selected,
+    Deselected,
+    Cleared(usize),
+    Mistake
+}
+
+#[derive(Clone)]
+pub struct MathBoard {
+    pub grid: Grid<Number>,
+    pub target: u32,
+    pub selected: Vec<(usize, usize)>,
+    rng: ChaCha8Rng
+}
+
+impl MathBoard {
+    // Same seed and grid dimensions always produce the same numbers and targets
+    pub fn new(width: usize, height: usize, seed: u64) -> MathBoard {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut grid = Grid::new(width, height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let value = rng.random_range(1..=MAX_NUMBER);
+                let color = *NEON_COLORS.choose(&mut rng).unwrap();
+
+                grid.set(x, y, Some(Number { value, color }));
+            }
+        }
+
+        let mut board = MathBoard { grid, target: 0, selected: Vec::new(), rng };
+        board.next_target();
+
+        board
+    }
+
+    pub fn selected_sum(&self) -> u32 {
+        self.selected.iter().filter_map(|&(x, y)| self.grid.get(x, y)).map(|number| number.value).sum()
+    }
+
+    pub fn click(&mut self, x: usize, y: usize) -> Option<MathClick> {
+        self.grid.get(x, y)?;
+
+        if let Some(index) = self.selected.iter().position(|&cell| cell == (x, y)) {
+            self.selected.remove(index);
+            return Some(MathClick::Deselected);
+        }
+
+        self.selected.push((x, y));
+
+        let sum = self.selected_sum();
+
+        if sum == self.target {
+            let cleared = self.selected.len();
+
+            for (x, y) in self.selected.drain(..) {
+                self.grid.remove(x, y);
+            }
+
+            if !self.grid.is_empty() {
+                self.next_target();
+            }
+
+            Some(MathClick::Cleared(cleared))
+        } else if sum > self.target {
+            // Every number is positive, so an overshooting selection can never come back down
+            self.selected.clear();
+
+            Some(MathClick::Mistake)
+        } else {
+            Some(MathClick::Selected)
+        }
+    }
+
+    // Target is the sum of a few numbers still on the board, so at least one valid selection always exists
+    fn next_target(&mut self) {
+        let remaining: Vec<u32> = self.grid.occupied().map(|(_, _, number)| number.value).collect();
+        let terms = self.rng.random_range(2..=MAX_TARGET_TERMS).min(remaining.len());
+
+        self.target = remaining.choose_multiple(&mut self.rng, terms).sum();
+    }
+}
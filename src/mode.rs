@@ -0,0 +1,26 @@
+// The rules a run is played with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Survival,
+    Timer,
+    Math,
+    Zen
+}
+
+impl Mode {
+    pub const ALL: [Mode; 4] = [Mode::Survival, Mode::Timer, Mode::Math, Mode::Zen];
+
+    // Name runs and replays are saved under
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Survival => "Survival",
+            Mode::Timer => "Timer",
+            Mode::Math => "Math",
+            Mode::Zen => "Zen"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Mode> {
+        Mode::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+}
@@ -0,0 +1,46 @@
+use std::collections::VecDeque;
+
+const NOTICE_SECONDS: f32 = 5.0;
+
+// Message shown at the bottom of the screen for a few seconds, used to report files that could not be read or saved.
+// Messages that come in while one is shown wait for their turn
+pub struct Notice {
+    text: String,
+    remaining: f32,
+    queued: VecDeque<String>
+}
+
+impl Notice {
+    pub fn new() -> Notice {
+        Notice { text: String::new(), remaining: 0.0, queued: VecDeque::new() }
+    }
+
+    pub fn show(&mut self, text: String) {
+        if self.remaining > 0.0 {
+            self.queued.push_back(text);
+        } else {
+            self.text = text;
+            self.remaining = NOTICE_SECONDS;
+        }
+    }
+
+    pub fn show_all(&mut self, texts: Vec<String>) {
+        texts.into_iter().for_each(|text| self.show(text));
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.remaining = (self.remaining - dt).max(0.0);
+
+        if self.remaining == 0.0 && let Some(text) = self.queued.pop_front() {
+            self.show(text);
+        }
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        if self.remaining > 0.0 {
+            Some(&self.text)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use macroquad::prelude::*;
+
+use arrow_puzzle::math::*;
+use crate::arrow::neon_color;
+
+pub fn draw_number(x: f32, y: f32, number: &Number, selected: bool, cell_size: f32) {
+    let font_size = cell_size * 0.6;
+    let text = number.value.to_string();
+    let text_dims = measure_text(&text, None, font_size as u16, 1.0);
+    let color = neon_color(number.color);
+
+    if selected {
+        draw_rectangle(x + 4.0, y + 4.0, cell_size - 8.0, cell_size - 8.0, Color::new(1.0, 1.0, 1.0, 0.2));
+        draw_rectangle_lines(x + 4.0, y + 4.0, cell_size - 8.0, cell_size - 8.0, 3.0, color);
+    }
+
+    draw_text(&text, x + (cell_size - text_dims.width) / 2.0, y + (cell_size + text_dims.offset_y) / 2.0, font_size, color);
+}
+
+pub fn draw_number_grid(board: &MathBoard, cell_size: f32, offset: Vec2) {
+    for (x, y, number) in board.grid.occupied() {
+        let px = offset.x + x as f32 * cell_size;
+        let py = offset.y + y as f32 * cell_size;
+
+        draw_number(px, py, number, board.selected.contains(&(x, y)), cell_size);
+    }
+}
@@ -0,0 +1,150 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const APP_DIR: &str = "arrow_puzzle";
+const DATA_DIR_VAR: &str = "ARROW_PUZZLE_DATA_DIR";
+const CONFIG_DIR_VAR: &str = "ARROW_PUZZLE_CONFIG_DIR";
+const DATA_DIR_FLAG: &str = "--data-dir";
+const CONFIG_DIR_FLAG: &str = "--config-dir";
+const REPLAY_DIR: &str = "replays";
+const ADOPTED_MARKER: &str = "legacy_files_adopted";
+
+// Where save files and assets live, independent of the directory the game was started from
+pub struct Paths {
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+    assets_dir: PathBuf
+}
+
+impl Paths {
+    // Command line flags win over environment variables, which win over the platform directories.
+    // Directories that can not be created are added to the warnings for the player to see
+    pub fn resolve(warnings: &mut Vec<String>) -> Paths {
+        let args: Vec<String> = env::args().collect();
+
+        let config_dir = flag(&args, CONFIG_DIR_FLAG)
+            .or_else(|| env::var_os(CONFIG_DIR_VAR).map(PathBuf::from))
+            .unwrap_or_else(|| platform_config_dir().join(APP_DIR));
+        let data_dir = flag(&args, DATA_DIR_FLAG)
+            .or_else(|| env::var_os(DATA_DIR_VAR).map(PathBuf::from))
+            .unwrap_or_else(|| platform_data_dir().join(APP_DIR));
+
+        for dir in [&config_dir, &data_dir.join(REPLAY_DIR)] {
+            if let Err(e) = fs::create_dir_all(dir) {
+                warnings.push(format!("Could not create {}: {}", dir.display(), e));
+            }
+        }
+
+        Paths { config_dir, data_dir, assets_dir: assets_dir() }
+    }
+
+    pub fn profiles(&self) -> PathBuf {
+        self.config_dir.join("profiles.json")
+    }
+
+    pub fn legacy_settings(&self) -> PathBuf {
+        self.config_dir.join("settings.json")
+    }
+
+    pub fn scoreboard(&self) -> PathBuf {
+        self.data_dir.join("scoreboard.json")
+    }
+
+    pub fn replays(&self) -> PathBuf {
+        self.data_dir.join(REPLAY_DIR)
+    }
+
+    // Scoreboard entries store the replay file name, older entries stored a path relative to the working directory
+    pub fn replay(&self, stored: &str) -> PathBuf {
+        let file_name = Path::new(stored).file_name().unwrap_or(stored.as_ref());
+
+        self.replays().join(file_name)
+    }
+
+    pub fn asset(&self, relative: &str) -> PathBuf {
+        self.assets_dir.join(relative)
+    }
+
+    // Save files used to be written to the working directory, copy them over once so nobody loses their scores.
+    // A marker is left once everything was copied, so files deleted later are not brought back from an old working directory
+    pub fn adopt_legacy_files(&self, warnings: &mut Vec<String>) {
+        let marker = self.config_dir.join(ADOPTED_MARKER);
+
+        if marker.exists() {
+            return;
+        }
+
+        let mut moves = vec![
+            (PathBuf::from("settings.json"), self.legacy_settings()),
+            (PathBuf::from("profiles.json"), self.profiles()),
+            (PathBuf::from("scoreboard.json"), self.scoreboard()),
+            (PathBuf::from("scoreboard.json.backup"), self.data_dir.join("scoreboard.json.backup"))
+        ];
+
+        if let Ok(replays) = fs::read_dir(REPLAY_DIR) {
+            for replay in replays.flatten() {
+                moves.push((replay.path(), self.replays().join(replay.file_name())));
+            }
+        }
+
+        let mut adopted = true;
+
+        for (legacy, target) in moves {
+            if legacy.is_file() && !target.exists() && !same_file(&legacy, &target) {
+                match fs::copy(&legacy, &target) {
+                    Ok(_) => println!("Copied {} to {}", legacy.display(), target.display()),
+                    Err(e) => {
+                        warnings.push(format!("Could not copy {}: {}", legacy.display(), e));
+                        adopted = false;
+                    }
+                }
+            }
+        }
+
+        // Whatever failed to copy is tried again on the next launch
+        if adopted && let Err(e) = fs::write(&marker, "") {
+            warnings.push(format!("Could not create {}: {}", marker.display(), e));
+        }
+    }
+}
+
+fn flag(args: &[String], name: &str) -> Option<PathBuf> {
+    args.iter().position(|arg| arg == name).and_then(|i| args.get(i + 1)).map(PathBuf::from)
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+}
+
+fn home_dir() -> PathBuf {
+    env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).map(PathBuf::from).unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn platform_config_dir() -> PathBuf {
+    if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from).unwrap_or_else(home_dir)
+    } else if cfg!(target_os = "macos") {
+        home_dir().join("Library/Application Support")
+    } else {
+        env::var_os("XDG_CONFIG_HOME").map(PathBuf::from).filter(|dir| dir.is_absolute()).unwrap_or_else(|| home_dir().join(".config"))
+    }
+}
+
+fn platform_data_dir() -> PathBuf {
+    if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from).unwrap_or_else(home_dir)
+    } else if cfg!(target_os = "macos") {
+        home_dir().join("Library/Application Support")
+    } else {
+        env::var_os("XDG_DATA_HOME").map(PathBuf::from).filter(|dir| dir.is_absolute()).unwrap_or_else(|| home_dir().join(".local/share"))
+    }
+}
+
+// Assets shipped next to the executable, falling back to the working directory for `cargo run`
+fn assets_dir() -> PathBuf {
+    env::current_exe().ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("assets")))
+        .filter(|dir| dir.is_dir())
+        .unwrap_or_else(|| PathBuf::from("assets"))
+}
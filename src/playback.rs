@@ -0,0 +1,185 @@
+use crate::countdown::Countdown;
+use crate::game::Game;
+use crate::mode::Mode;
+use crate::replay::*;
+
+pub const PLAYBACK_SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+
+// Rebuilds a run from its replay by dealing the same boards from the seed and feeding the recorded actions to a Game in order
+pub struct Playback {
+    pub replay: Replay,
+    pub mode: Mode,
+    pub time: f32,
+    pub speed: f32,
+    pub playing: bool,
+    pub game: Game,
+    checkpoints: Vec<Checkpoint>, // The game at the start of every board reached so far, so scrubbing back does not deal the boards again
+    applied: usize // Events already applied
+}
+
+struct Checkpoint {
+    applied: usize,
+    game: Game
+}
+
+impl Playback {
+    pub fn new(replay: Replay, mode: Mode) -> Playback {
+        // A timer run lasts exactly as long as its timer, the countdown was never recorded
+        let mut game = Game::with_seed(mode, replay.seed, replay.difficulty, replay.grid_width, replay.grid_height, replay.duration);
+
+        game.countdown = Countdown::finished();
+
+        Playback {
+            mode,
+            time: 0.0,
+            speed: 1.0,
+            playing: true,
+            checkpoints: vec![Checkpoint { applied: 0, game: game.clone() }],
+            applied: 0,
+            game,
+            replay
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.time >= self.replay.duration
+    }
+
+    // Value the nav bar shows as timer, the timer mode counted down to zero
+    pub fn timer(&self) -> f32 {
+        self.game.timer.max(0.0)
+    }
+
+    pub fn toggle_playing(&mut self) {
+        if self.finished() {
+            self.seek(0.0);
+        }
+
+        self.playing = !self.playing;
+    }
+
+    // Steps to the next speed, wrapping back to the slowest
+    pub fn next_speed(&mut self) {
+        let index = PLAYBACK_SPEEDS.iter().position(|&speed| speed == self.speed).unwrap_or(0);
+
+        self.speed = PLAYBACK_SPEEDS[(index + 1) % PLAYBACK_SPEEDS.len()];
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if !self.playing {
+            return;
+        }
+
+        self.seek(self.time + dt * self.speed);
+
+        if self.finished() {
+            self.playing = false;
+        }
+    }
+
+    // Going backwards starts over from the last board dealt before that time, going forwards only applies the events in between
+    pub fn seek(&mut self, time: f32) {
+        let time = time.clamp(0.0, self.replay.duration);
+
+        if time < self.time && let Some(checkpoint) = self.checkpoints.iter().rev().find(|checkpoint| checkpoint.game.replay.duration <= time) {
+            self.game = checkpoint.game.clone();
+            self.applied = checkpoint.applied;
+        }
+
+        self.time = time;
+
+        while let Some(event) = self.replay.events.get(self.applied) && event.time <= time {
+            let event = event.clone();
+
+            self.play_until(event.time);
+            self.apply(&event.action);
+            self.applied += 1;
+
+            if matches!(event.action, ReplayAction::NewBoard) && self.checkpoints.last().is_some_and(|checkpoint| checkpoint.applied < self.applied) {
+                self.checkpoints.push(Checkpoint { applied: self.applied, game: self.game.clone() });
+            }
+        }
+
+        self.play_until(time);
+    }
+
+    // The game's own replay counts the play time it has been ticked through
+    fn play_until(&mut self, time: f32) {
+        self.game.tick((time - self.game.replay.duration).max(0.0));
+    }
+
+    fn apply(&mut self, action: &ReplayAction) {
+        match action {
+            ReplayAction::Click { x, y, .. } => self.game.click(*x, *y),
+            ReplayAction::Hint { .. } => self.game.hint(),
+            ReplayAction::NewBoard => self.game.next_board(),
+            ReplayAction::Undo => self.game.undo(),
+            ReplayAction::Redo => self.game.redo()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::Difficulty;
+    use crate::grid::*;
+
+    // Removes arrows, asks for hints, clicks blocked arrows and in Zen takes removals back, dealing new boards as they are cleared
+    fn play(mode: Mode) -> Game {
+        let mut game = Game::started(mode, 11, Difficulty::Easy);
+
+        for step in 0..80 {
+            game.tick(0.1);
+
+            if game.board_cleared() {
+                game.next_board();
+                continue;
+            }
+
+            let blocked = game.grid.occupied().map(|(x, y, _)| (x, y)).find(|&(x, y)| !can_remove(&game.grid, x, y));
+
+            match (step % 7, blocked) {
+                (0, _) => { game.hint(); }
+                (3, _) if mode == Mode::Zen => { game.undo(); }
+                (4, _) if mode == Mode::Zen => { game.redo(); }
+                (5, Some((x, y))) => { game.click(x, y); }
+                _ => if let Some((x, y)) = find_hint(&game.grid) { game.click(x, y); }
+            }
+
+            if game.is_over() {
+                break;
+            }
+        }
+
+        game
+    }
+
+    fn cells(game: &Game) -> Vec<(usize, usize)> {
+        game.grid.occupied().map(|(x, y, _)| (x, y)).collect()
+    }
+
+    #[test]
+    fn playback_ends_where_the_run_ended() {
+        for mode in [Mode::Survival, Mode::Zen] {
+            let game = play(mode);
+            let mut playback = Playback::new(game.replay.clone(), mode);
+
+            assert!(game.replay.events.iter().any(|event| matches!(event.action, ReplayAction::NewBoard)));
+
+            playback.seek(game.replay.duration);
+
+            assert_eq!(playback.game.scoring.total(), game.scoring.total());
+            assert_eq!(playback.game.scoring.mistakes, game.scoring.mistakes);
+            assert_eq!(playback.game.health, game.health);
+            assert_eq!(cells(&playback.game), cells(&game));
+
+            // Scrubbing back to an earlier board and forward again lands on the same state
+            playback.seek(0.5);
+            playback.seek(game.replay.duration);
+
+            assert_eq!(playback.game.scoring.total(), game.scoring.total());
+            assert_eq!(cells(&playback.game), cells(&game));
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::Difficulty;
+use crate::math::MathClick;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ClickOutcome {
+    Removed,
+    Mistake,
+    Selected,
+    Deselected,
+    Cleared(usize)
+}
+
+impl From<&MathClick> for ClickOutcome {
+    fn from(click: &MathClick) -> ClickOutcome {
+        match click {
+            MathClick::Selected => ClickOutcome::Selected,
+            MathClick::Deselected => ClickOutcome::Deselected,
+            MathClick::Cleared(count) => ClickOutcome::Cleared(*count),
+            MathClick::Mistake => ClickOutcome::Mistake
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum ReplayAction {
+    Click { x: usize, y: usize, outcome: ClickOutcome },
+    Hint { x: usize, y: usize },
+    NewBoard,
+    Undo,
+    Redo
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReplayEvent {
+    pub time: f32, // Seconds of play since the countdown ended
+    pub action: ReplayAction
+}
+
+// Everything needed to regenerate the boards of a run and step through what the player did
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Replay {
+    pub gamemode: String,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub grid_width: usize,
+    pub grid_height: usize,
+    pub duration: f32,
+    pub events: Vec<ReplayEvent>
+}
+
+impl Replay {
+    pub fn new(gamemode: &str, seed: u64, difficulty: Difficulty, grid_width: usize, grid_height: usize) -> Replay {
+        Replay { gamemode: gamemode.to_string(), seed, difficulty, grid_width, grid_height, duration: 0.0, events: Vec::new() }
+    }
+
+    pub fn tick(&mut self, dt: f32) {
+        self.duration += dt;
+    }
+
+    pub fn record(&mut self, action: ReplayAction) {
+        self.events.push(ReplayEvent { time: self.duration, action });
+    }
+}
@@ -0,0 +1,149 @@
+const STREAK_STEP: u32 = 5; // Correct clicks in a row needed to raise the multiplier by one
+const MAX_MULTIPLIER: u32 = 5;
+const GRID_CLEAR_BONUS: i32 = 10;
+const PAR_SECONDS_PER_CELL: f32 = 1.0; // Clearing a board faster than this earns the fast clear bonus
+const HINTS_PER_RUN: u32 = 3;
+const HINT_PENALTY: i32 = 5;
+
+// Points one removal earned, kept with it so undoing and redoing it takes back or gives back exactly these
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Points {
+    pub base: i32,
+    pub streak_bonus: i32,
+    pub grid_bonus: i32,
+    pub fast_clear_bonus: i32
+}
+
+impl std::ops::Add for Points {
+    type Output = Points;
+
+    fn add(self, other: Points) -> Points {
+        Points {
+            base: self.base + other.base,
+            streak_bonus: self.streak_bonus + other.streak_bonus,
+            grid_bonus: self.grid_bonus + other.grid_bonus,
+            fast_clear_bonus: self.fast_clear_bonus + other.fast_clear_bonus
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Scoring {
+    pub base: i32,
+    pub streak_bonus: i32,
+    pub grid_bonus: i32,
+    pub fast_clear_bonus: i32,
+    pub hint_penalty: i32,
+    pub hints_used: u32,
+    pub clicks: u32,
+    pub mistakes: u32,
+    pub streak: u32,
+    pub best_streak: u32,
+    board_cells: usize,
+    board_time: f32
+}
+
+impl Scoring {
+    pub fn new(board_cells: usize) -> Scoring {
+        Scoring {
+            base: 0,
+            streak_bonus: 0,
+            grid_bonus: 0,
+            fast_clear_bonus: 0,
+            hint_penalty: 0,
+            hints_used: 0,
+            clicks: 0,
+            mistakes: 0,
+            streak: 0,
+            best_streak: 0,
+            board_cells,
+            board_time: 0.0
+        }
+    }
+
+    pub fn total(&self) -> i32 {
+        self.base + self.streak_bonus + self.grid_bonus + self.fast_clear_bonus - self.hint_penalty
+    }
+
+    pub fn hints_left(&self) -> u32 {
+        HINTS_PER_RUN.saturating_sub(self.hints_used)
+    }
+
+    // Returns false once the run is out of hints
+    pub fn use_hint(&mut self) -> bool {
+        if self.hints_left() == 0 {
+            return false;
+        }
+
+        self.hints_used += 1;
+        self.hint_penalty += HINT_PENALTY;
+        self.streak = 0;
+
+        true
+    }
+
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.streak / STREAK_STEP).min(MAX_MULTIPLIER)
+    }
+
+    // Every cell is worth one point, the multiplier adds the rest as streak bonus
+    pub fn correct(&mut self, cells: usize) -> Points {
+        let points = Points { base: cells as i32, streak_bonus: cells as i32 * (self.multiplier() as i32 - 1), ..Points::default() };
+
+        self.add(points);
+        self.streak += 1;
+        self.best_streak = self.best_streak.max(self.streak);
+
+        points
+    }
+
+    // Every click on a cell of the board, whatever it did
+    pub fn click(&mut self) {
+        self.clicks += 1;
+    }
+
+    pub fn mistake(&mut self) {
+        self.mistakes += 1;
+        self.streak = 0;
+    }
+
+    // Taking a removal back returns everything it earned and breaks the streak
+    pub fn undo(&mut self, points: Points) {
+        self.base -= points.base;
+        self.streak_bonus -= points.streak_bonus;
+        self.grid_bonus -= points.grid_bonus;
+        self.fast_clear_bonus -= points.fast_clear_bonus;
+        self.streak = 0;
+    }
+
+    // Redoing a removal earns what it earned the first time, without counting towards the streak again
+    pub fn redo(&mut self, points: Points) {
+        self.add(points);
+    }
+
+    fn add(&mut self, points: Points) {
+        self.base += points.base;
+        self.streak_bonus += points.streak_bonus;
+        self.grid_bonus += points.grid_bonus;
+        self.fast_clear_bonus += points.fast_clear_bonus;
+    }
+
+    pub fn tick(&mut self, dt: f32) {
+        self.board_time += dt;
+    }
+
+    pub fn grid_cleared(&mut self) -> Points {
+        let par = self.board_cells as f32 * PAR_SECONDS_PER_CELL;
+        let fast_clear_bonus = if self.board_time < par { (par - self.board_time).ceil() as i32 } else { 0 };
+        let points = Points { grid_bonus: GRID_CLEAR_BONUS, fast_clear_bonus, ..Points::default() };
+
+        self.add(points);
+
+        points
+    }
+
+    pub fn new_board(&mut self, board_cells: usize) {
+        self.board_cells = board_cells;
+        self.board_time = 0.0;
+    }
+}
@@ -0,0 +1,81 @@
+use crate::grid::*;
+
+// Arrows that could not be removed, with the moves that were possible before getting stuck
+#[derive(Debug)]
+pub struct Deadlock {
+    pub removed: Vec<(usize, usize)>,
+    pub stuck: Vec<(usize, usize)>
+}
+
+// Removing an arrow only ever clears paths, so an arrow that is removable stays removable.
+// Greedily removing whatever is free therefore clears the grid whenever any order would,
+// and getting stuck proves that no order exists.
+pub fn solve(grid: &Grid) -> Result<Vec<(usize, usize)>, Deadlock> {
+    let mut grid = grid.clone();
+    let mut order = Vec::new();
+
+    loop {
+        let free = removable_arrows(&grid);
+
+        if free.is_empty() {
+            break;
+        }
+
+        for (x, y) in free {
+            grid.remove(x, y);
+            order.push((x, y));
+        }
+    }
+
+    if grid.is_empty() {
+        Ok(order)
+    } else {
+        Err(Deadlock { removed: order, stuck: grid.occupied().map(|(x, y, _)| (x, y)).collect() })
+    }
+}
+
+pub fn removable_arrows(grid: &Grid) -> Vec<(usize, usize)> {
+    grid.occupied()
+        .map(|(x, y, _)| (x, y))
+        .filter(|&(x, y)| can_remove(grid, x, y))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::Difficulty;
+
+    fn arrow(dir: Direction) -> Option<Arrow> {
+        Some(Arrow { dir, color: NeonColor::Green })
+    }
+
+    #[test]
+    fn arrows_facing_each_other_are_stuck() {
+        let mut grid = Grid::new(3, 1);
+
+        grid.set(0, 0, arrow(Direction::Right));
+        grid.set(1, 0, arrow(Direction::Left));
+        grid.set(2, 0, arrow(Direction::Up));
+
+        let deadlock = solve(&grid).unwrap_err();
+
+        assert_eq!(deadlock.removed, [(2, 0)]);
+        assert_eq!(deadlock.stuck, [(0, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn solution_clears_generated_grids() {
+        for seed in 0..20 {
+            let mut grid = generate_grid_seeded(6, 5, seed, Some(Difficulty::Hard));
+
+            for (x, y) in solve(&grid).unwrap() {
+                assert!(can_remove(&grid, x, y));
+
+                grid.remove(x, y);
+            }
+
+            assert!(grid.is_empty());
+        }
+    }
+}
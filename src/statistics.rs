@@ -0,0 +1,69 @@
+use crate::file::SaveData;
+
+// Totals over every saved run of one player
+pub struct PlayerStatistics {
+    pub player_name: String,
+    pub games_per_mode: Vec<(String, usize)>,
+    pub removed: u32,
+    pub clicks: u32,
+    pub mistakes: u32,
+    pub best_score: i32,
+    pub play_time: f32,
+    pub scores: Vec<i32> // In the order the runs were played
+}
+
+impl PlayerStatistics {
+    fn new(player_name: &str) -> PlayerStatistics {
+        PlayerStatistics { player_name: player_name.to_string(), games_per_mode: Vec::new(), removed: 0, clicks: 0, mistakes: 0, best_score: i32::MIN, play_time: 0.0, scores: Vec::new() }
+    }
+
+    fn add(&mut self, entry: &SaveData) {
+        match self.games_per_mode.iter_mut().find(|(gamemode, _)| *gamemode == entry.gamemode) {
+            Some((_, games)) => *games += 1,
+            None => self.games_per_mode.push((entry.gamemode.clone(), 1))
+        }
+
+        self.removed += entry.removed;
+        self.clicks += entry.clicks;
+        self.mistakes += entry.mistakes;
+        self.best_score = self.best_score.max(entry.score);
+        self.play_time += entry.time;
+        self.scores.push(entry.score);
+    }
+
+    pub fn games(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn average_score(&self) -> f32 {
+        self.scores.iter().sum::<i32>() as f32 / self.games() as f32
+    }
+
+    // Share of clicks that were not mistakes, None for runs saved before clicks were recorded
+    pub fn accuracy(&self) -> Option<f32> {
+        if self.clicks == 0 {
+            return None;
+        }
+
+        Some(self.clicks.saturating_sub(self.mistakes) as f32 / self.clicks as f32)
+    }
+}
+
+// One entry per player in the order they first appear in the scoreboard
+pub fn player_statistics(entries: &[SaveData]) -> Vec<PlayerStatistics> {
+    let mut players: Vec<PlayerStatistics> = Vec::new();
+
+    for entry in entries {
+        let index = match players.iter().position(|player| player.player_name == entry.player_name) {
+            Some(index) => index,
+            None => {
+                players.push(PlayerStatistics::new(&entry.player_name));
+                players.len() - 1
+            }
+        };
+
+        players[index].add(entry);
+    }
+
+    players
+}
@@ -0,0 +1,119 @@
+use macroquad::prelude::*;
+
+// Cross-fade played whenever the game state changes, the last frame of the outgoing screen is kept and faded out over the incoming one.
+// Macroquad draws the ui after everything else, so while fading the ui goes into a render target and is drawn a frame late under the outgoing screen
+pub struct Transition {
+    duration: f32,
+    elapsed: f32,
+    outgoing: Option<RenderTarget>,
+    ui_layers: Vec<RenderTarget>, // The first receives this frame's ui, the second holds the last one
+    ui_ready: bool // Whether the second layer holds a frame of the incoming screen yet
+}
+
+impl Transition {
+    pub fn new(duration: f32) -> Transition {
+        let duration = duration.max(0.0);
+
+        Transition { duration, elapsed: duration, outgoing: None, ui_layers: Vec::new(), ui_ready: false }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    // Called before anything is drawn, clearing a render target drops whatever was queued for the frame
+    pub fn begin_frame(&mut self) {
+        set_default_camera();
+
+        if let Some(layer) = self.ui_layers.first() && self.is_fading() {
+            set_camera(&target_camera(layer));
+            clear_background(BLANK);
+            set_default_camera();
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+
+        if !self.is_fading() {
+            self.outgoing = None;
+            self.ui_layers.clear();
+            self.ui_ready = false;
+        }
+    }
+
+    // Opacity of the outgoing screen, 1.0 right after the state changed and 0.0 once the fade is done
+    pub fn alpha(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+
+        (1.0 - self.elapsed / self.duration).clamp(0.0, 1.0)
+    }
+
+    fn is_fading(&self) -> bool {
+        self.outgoing.is_some() && self.alpha() > 0.0
+    }
+
+    // Called last in a frame. On the frame the state changed it keeps that frame as the outgoing screen and starts the fade
+    pub fn draw(&mut self, state_changed: bool) {
+        let fading = self.is_fading();
+
+        if let Some(outgoing) = &self.outgoing && fading {
+            if self.ui_ready {
+                draw_target(&self.ui_layers[1], WHITE);
+            }
+
+            draw_target(outgoing, Color::new(1.0, 1.0, 1.0, self.alpha()));
+        }
+
+        if state_changed {
+            self.capture();
+        } else if fading {
+            // The ui drawn after this call lands in the first layer, which is shown next frame
+            set_camera(&target_camera(&self.ui_layers[0]));
+            self.ui_layers.swap(0, 1);
+            self.ui_ready = true;
+        }
+    }
+
+    fn capture(&mut self) {
+        if self.duration <= 0.0 {
+            return;
+        }
+
+        // Reading the screen draws everything queued so far, so the render targets can be cleared without losing any of it
+        let screen = Texture2D::from_image(&get_screen_data());
+        let outgoing = render_target(screen.width() as u32, screen.height() as u32);
+
+        self.ui_layers = (0..2).map(|_| render_target(screen.width() as u32, screen.height() as u32)).collect();
+
+        for layer in &self.ui_layers {
+            set_camera(&target_camera(layer));
+            clear_background(BLANK);
+        }
+
+        // The ui of this frame is drawn after this call, so it ends up in the outgoing screen as well
+        set_camera(&target_camera(&outgoing));
+        draw_texture_ex(&screen, 0.0, 0.0, WHITE, DrawTextureParams { dest_size: Some(screen.size()), flip_y: true, ..Default::default() });
+
+        self.outgoing = Some(outgoing);
+        self.ui_ready = false;
+        self.elapsed = 0.0;
+    }
+}
+
+// Lays the render target out like the screen, so anything drawn into it keeps its screen position
+fn target_camera(target: &RenderTarget) -> Camera2D {
+    let size = target.texture.size();
+    let mut camera = Camera2D::from_display_rect(Rect::new(0.0, 0.0, size.x, size.y));
+
+    camera.render_target = Some(target.clone());
+
+    camera
+}
+
+// Render targets are stored upside down compared to the screen
+fn draw_target(target: &RenderTarget, color: Color) {
+    draw_texture_ex(&target.texture, 0.0, 0.0, color, DrawTextureParams { dest_size: Some(vec2(screen_width(), screen_height())), flip_y: true, ..Default::default() });
+}
@@ -1,12 +1,15 @@
 use macroquad::prelude::*;
 use macroquad::ui::{root_ui, Skin};
 
+use crate::assets::load_asset;
+use arrow_puzzle::paths::Paths;
+
 pub struct SkinLoader {
     skin: Skin
 }
 
 impl SkinLoader {
-    pub async fn new() -> SkinLoader {
+    pub async fn new(paths: &Paths) -> SkinLoader {
         let label_style = root_ui()
             .style_builder()
             .text_color(Color::from_rgba(120, 120, 120, 255))
@@ -15,29 +18,29 @@ impl SkinLoader {
 
         let window_style = root_ui()
             .style_builder()
-            .background(Image::from_file_with_format(include_bytes!("../../assets/ui/window_background.png"), None).unwrap())
+            .background(ui_image(paths, "window_background.png"))
             .build();
 
         let button_style = root_ui()
             .style_builder()
-            .background(Image::from_file_with_format(include_bytes!("../../assets/ui/button_background.png"), None).unwrap())
+            .background(ui_image(paths, "button_background.png"))
             .background_margin(RectOffset::new(8.0, 8.0, 8.0, 8.0))
-            .background_hovered(Image::from_file_with_format(include_bytes!("../../assets/ui/button_hovered_background.png"), None).unwrap())
-            .background_clicked(Image::from_file_with_format(include_bytes!("../../assets/ui/button_clicked_background.png"), None).unwrap())
+            .background_hovered(ui_image(paths, "button_hovered_background.png"))
+            .background_clicked(ui_image(paths, "button_clicked_background.png"))
             .text_color(Color::from_rgba(180, 180, 100, 255))
             .font_size(25)
             .build();
 
         let checkbox_style = root_ui()
             .style_builder()
-            .background(Image::from_file_with_format(include_bytes!("../../assets/ui/checkbox_background.png"), None).unwrap())
-            .background_hovered(Image::from_file_with_format(include_bytes!("../../assets/ui/checkbox_hovered_background.png"), None).unwrap())
-            .background_clicked(Image::from_file_with_format(include_bytes!("../../assets/ui/checkbox_clicked_background.png"), None).unwrap())
+            .background(ui_image(paths, "checkbox_background.png"))
+            .background_hovered(ui_image(paths, "checkbox_hovered_background.png"))
+            .background_clicked(ui_image(paths, "checkbox_clicked_background.png"))
             .build();
 
         let editbox_style = root_ui()
             .style_builder()
-            .background(Image::from_file_with_format(include_bytes!("../../assets/ui/editbox_background.png"), None).unwrap())
+            .background(ui_image(paths, "editbox_background.png"))
             .background_margin(RectOffset::new(2.0, 2.0, 2.0, 2.0))
             .text_color(Color::from_rgba(120, 120, 120, 255))
             .font_size(25)
@@ -45,7 +48,7 @@ impl SkinLoader {
 
         let combobox_style = root_ui()
             .style_builder()
-            .background(Image::from_file_with_format(include_bytes!("../../assets/ui/combobox_background.png"), None).unwrap())
+            .background(ui_image(paths, "combobox_background.png"))
             .background_margin(RectOffset::new(4.0, 25.0, 6.0, 6.0))
             .text_color(Color::from_rgba(120, 120, 120, 255))
             .color(Color::from_rgba(210, 210, 210, 255))
@@ -66,8 +69,13 @@ impl SkinLoader {
     }
 
     pub fn get_skin(&self) -> Skin {
-        let skin = self.skin.clone();
-
-        skin
+        self.skin.clone()
     }
-}
\ No newline at end of file
+}
+
+// A missing or broken image becomes a flat gray background instead of stopping the game
+fn ui_image(paths: &Paths, name: &str) -> Image {
+    load_asset(paths, &format!("ui/{}", name))
+        .and_then(|bytes| Image::from_file_with_format(&bytes, None).ok())
+        .unwrap_or_else(|| Image::gen_image_color(1, 1, Color::from_rgba(60, 60, 60, 255)))
+}